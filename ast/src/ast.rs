@@ -1,4 +1,8 @@
+use num_bigint::BigInt;
+
 pub type Program = Vec<Decl>;
+/// A class constraint `C a` on a type variable
+pub type Constraint = (String, String);
 type IntType = i64;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -7,11 +11,43 @@ pub enum List<T> {
     Empty,
 }
 
+/// A module of a program, read from a file of its own
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    pub name: String,
+    /// The values the module exports, all of its own when it has no export list
+    pub exports: Option<Vec<String>>,
+    pub imports: Vec<Import>,
+    pub program: Program,
+}
+
+/// An `import` of another module
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub module: String,
+    /// Whether the imported values may only be referred to qualified
+    pub qualified: bool,
+    /// The name qualifying the imported values, which is the module's unless given with `as`
+    pub alias: String,
+    pub names: ImportList,
+}
+
+/// The values an import brings into scope. Types, classes and constructors are always in scope.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportList {
+    All,
+    Only(Vec<String>),
+    Hiding(Vec<String>),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Decl {
     TypeAlias(String, Type),
-    TypeSignature(String, Type),
+    TypeSignature(String, Vec<Constraint>, Type),
     FunDecl(String, Vec<String>, Expr),
+    DataDecl(String, Vec<String>, Vec<(String, Vec<Type>)>),
+    ClassDecl(Vec<Constraint>, String, String, Vec<Decl>),
+    InstanceDecl(Vec<Constraint>, String, Type, Vec<Decl>),
     SExpr(Expr),
     EndOfInstruction,
 }
@@ -22,30 +58,71 @@ pub enum Type {
     Function(Box<Type>, Box<Type>),
     Tuple(Vec<Type>),
     List(Box<Type>),
+    /// An action which may perform input and output when run
+    IO(Box<Type>),
+    Constructor(String, Vec<Type>),
     Int,
+    Integer,
+    Double,
     Bool,
     Char,
+    /// Written `String`, which the typechecker expands to a list of characters
     String,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Var(String),
+    Constructor(String),
     Application(Box<Expr>, Box<Expr>),
     If(Box<Expr>, Box<Expr>, Box<Expr>),
-    Let(String, Box<Expr>, Box<Expr>),
+    /// A group of bindings, which may refer to each other, scoping over an expression
+    Let(Vec<Decl>, Box<Expr>),
     Lambda(String, Box<Expr>),
-    Case(Box<Expr>, Vec<(Pattern, Expr)>),
+    Case(Box<Expr>, Vec<(Pattern, Rhs)>),
     BinOp(Box<Expr>, Op, Box<Expr>),
+    /// An operator used as a curried function, as in `(+)`
+    Operator(Op),
+    /// Operands separated by infix operators, as written. The parser groups them according to the
+    /// fixities of the operators, so later stages never see this.
+    Infix(Box<Expr>, Vec<(InfixOp, Expr)>),
+    /// A negated operand of an infix expression, which binds like `-` when grouping by fixity
+    Negate(Box<Expr>),
     Tuple(Vec<Expr>),
     List(List<Expr>),
-    Range(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    /// An arithmetic sequence `[from, then .. to]` of Ints or Chars
+    Range(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
     Literal(Literal),
+    /// An expression with the type it is annotated with, as in `e :: t`. The typechecker erases
+    /// annotations, and the evaluator ignores them in programs it did not check.
+    Annotated(Box<Expr>, Box<Type>),
+}
+
+/// The right-hand side of an equation or case alternative
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rhs {
+    Expr(Expr),
+    /// Expressions tried in order, taking the first whose guards all succeed. When none does, the
+    /// next alternative is tried.
+    Guarded(Vec<(Vec<Guard>, Expr)>),
+    /// Bindings of a `where` block, scoping over all guards of the right-hand side
+    Where(Vec<Decl>, Box<Rhs>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Guard {
+    /// A boolean guard `e`
+    Bool(Expr),
+    /// A pattern guard `p <- e`, binding the variables of `p` in later guards and the body
+    Pattern(Pattern, Expr),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     Int(IntType),
+    /// An arbitrary-precision integer, or an integer literal too large for `Int`
+    Integer(BigInt),
+    Double(f64),
     Bool(bool),
     String(String),
     Char(char),
@@ -60,6 +137,38 @@ pub enum Pattern {
     Tuple(Vec<Pattern>),
     FakeTuple(Vec<Pattern>),
     List(Box<Pattern>, Box<Pattern>),
+    Constructor(String, Vec<Pattern>),
+    /// An as-pattern `x@p`, binding `x` to the whole value `p` matches
+    As(String, Box<Pattern>),
+    /// A bang pattern `!p`, forcing the value before matching it
+    Bang(Box<Pattern>),
+    /// A lazy pattern `~p`, which always matches and only takes the value apart once one of its
+    /// variables is demanded
+    Lazy(Box<Pattern>),
+}
+
+impl Pattern {
+    /// The variables the pattern binds, from left to right
+    pub fn vars(&self) -> Vec<String> {
+        match self {
+            Pattern::Var(name) => vec![name.clone()],
+            Pattern::As(name, p) => [vec![name.clone()], p.vars()].concat(),
+            Pattern::Bang(p) | Pattern::Lazy(p) => p.vars(),
+            Pattern::Tuple(ps) | Pattern::FakeTuple(ps) | Pattern::Constructor(_, ps) => {
+                ps.iter().flat_map(Pattern::vars).collect()
+            }
+            Pattern::List(p1, p2) => [p1.vars(), p2.vars()].concat(),
+            Pattern::Literal(_) | Pattern::Wildcard | Pattern::EmptyList => vec![],
+        }
+    }
+}
+
+/// An operator written between its operands
+#[derive(Debug, Clone, PartialEq)]
+pub enum InfixOp {
+    Builtin(Op),
+    /// An operator defined in the program, or an identifier in backticks
+    Named(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -1,9 +1,13 @@
+use crate::ast::Constraint;
 use crate::ast::Decl;
 use crate::ast::Expr;
+use crate::ast::Guard;
+use crate::ast::InfixOp;
 use crate::ast::List;
 use crate::ast::Literal;
 use crate::ast::Op;
 use crate::ast::Pattern;
+use crate::ast::Rhs;
 use crate::ast::Type;
 use core::fmt::{Display, Formatter, Result};
 
@@ -13,6 +17,7 @@ impl Display for Type {
             Type::TypeVariable(name) => write!(f, "{}", name),
             Type::Function(left, right) => write!(f, "({} -> {})", left, right),
             Type::List(t) => write!(f, "[{}]", t),
+            Type::IO(t) => write!(f, "(IO {})", t),
             Type::Tuple(vals) => write!(
                 f,
                 "({})",
@@ -21,7 +26,11 @@ impl Display for Type {
                     .collect::<Vec<_>>()
                     .join(", ")
             ),
+            Type::Constructor(name, args) if args.is_empty() => write!(f, "{}", name),
+            Type::Constructor(name, args) => fmt_vec(f, args, &format!("({} ", name), ")", " "),
             Type::Int => write!(f, "Int"),
+            Type::Integer => write!(f, "Integer"),
+            Type::Double => write!(f, "Double"),
             Type::Bool => write!(f, "Bool"),
             Type::Char => write!(f, "Char"),
             Type::String => write!(f, "String"),
@@ -33,9 +42,22 @@ impl Display for Literal {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
             Literal::Int(val) => write!(f, "{}", val),
+            Literal::Integer(val) => write!(f, "{}", val),
+            Literal::Double(val) => write!(f, "{}", show_double(*val)),
             Literal::Bool(val) => write!(f, "{}", if *val {"True"} else {"False"}),
-            Literal::String(val) => write!(f, "\"{}\"", val),
-            Literal::Char(val) => write!(f, "'{}'", val),
+            Literal::String(val) => {
+                write!(f, "\"")?;
+                let mut chars = val.chars().peekable();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        c => write!(f, "{}", escape(c, chars.peek().copied()))?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Literal::Char('\'') => write!(f, "'\\''"),
+            Literal::Char(val) => write!(f, "'{}'", escape(*val, None)),
         }
     }
 }
@@ -44,10 +66,40 @@ impl Display for Decl {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
             Decl::TypeAlias(type_var, type_expr) => write!(f, "type {} = {}", type_var, type_expr),
-            Decl::TypeSignature(var_name, type_expr) => write!(f, "{} :: {}", var_name, type_expr),
+            Decl::TypeSignature(var_name, context, type_expr) => {
+                write!(f, "{} :: {}{}", var_name, fmt_context(context), type_expr)
+            }
             Decl::FunDecl(var_name, args, expr) => {
                 write!(f, "{} {}= {}", var_name, args.join(" ") + " ", expr)
             }
+            Decl::DataDecl(name, params, constructors) => {
+                write!(f, "data {}", name)?;
+                for param in params {
+                    write!(f, " {}", param)?;
+                }
+                let constructors = constructors
+                    .iter()
+                    .map(|(con, fields)| {
+                        let fields = fields.iter().map(|t| format!(" {}", t));
+                        format!("{}{}", con, fields.collect::<String>())
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, " = {}", constructors.join(" | "))
+            }
+            Decl::ClassDecl(context, class, var, decls) => {
+                write!(f, "class {}{} {} where ", fmt_context(context), class, var)?;
+                fmt_vec(f, decls, "{ ", " }", "; ")
+            }
+            Decl::InstanceDecl(context, class, instance_type, decls) => {
+                write!(
+                    f,
+                    "instance {}{} {} where ",
+                    fmt_context(context),
+                    class,
+                    instance_type
+                )?;
+                fmt_vec(f, decls, "{ ", " }", "; ")
+            }
             Decl::SExpr(expr) => write!(f, "{}", expr),
             Decl::EndOfInstruction => write!(f, ""),
         }
@@ -58,25 +110,75 @@ impl Display for Expr {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
             Expr::Var(name) => write!(f, "{}", name),
+            Expr::Constructor(name) => write!(f, "{}", name),
             Expr::Application(fun, arg) => write!(f, "({} {})", fun, arg),
             Expr::If(a, b, c) => write!(f, "if {}, then {}, else {}", a, b, c),
             Expr::Tuple(es) => fmt_vec(f, es, "(", ")", ", "),
             Expr::List(ls) => write!(f, "{}", ls),
             Expr::BinOp(l, op, r) => write!(f, "({} {} {})", l, op, r),
+            Expr::Operator(op) => write!(f, "({})", op),
+            Expr::Infix(first, rest) => {
+                write!(f, "({}", first)?;
+                for (op, e) in rest {
+                    write!(f, " {} {}", op, e)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Negate(e) => write!(f, "-{}", e),
+            Expr::Annotated(e, t) => write!(f, "({} :: {})", e, t),
             Expr::Literal(l) => write!(f, "{}", l),
             Expr::Case(e, cases) => {
                 write!(f, "case {} of {{", e)?;
-                for (p, body) in cases {
-                    write!(f, "{} -> {}; ", p, body)?;
+                for (p, rhs) in cases {
+                    write!(f, "{}{}; ", p, rhs)?;
                 }
                 write!(f, "}}")
             }
-            Expr::Range(start, step, last) => match last {
-                Some(last) => write!(f, "[{}, ..{}.., {}] ", start, step, last),
-                None => write!(f, "[{}, ..{}..] ", start, step),
-            },
+            Expr::Range(from, then, to) => {
+                write!(f, "[{}", from)?;
+                if let Some(then) = then {
+                    write!(f, ", {}", then)?;
+                }
+                write!(f, " ..")?;
+                if let Some(to) = to {
+                    write!(f, " {}", to)?;
+                }
+                write!(f, "]")
+            }
             Expr::Lambda(arg, expr) => write!(f, "(\\{} -> {})", arg, expr),
-            Expr::Let(x, expr1, expr2) => write!(f, "let {} = {} in {}", x, expr1, expr2),
+            Expr::Let(decls, expr) => {
+                write!(f, "let ")?;
+                fmt_vec(f, decls, "{ ", " }", "; ")?;
+                write!(f, " in {}", expr)
+            }
+        }
+    }
+}
+
+impl Display for Rhs {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Rhs::Expr(e) => write!(f, " -> {}", e),
+            Rhs::Guarded(alternatives) => {
+                for (guards, e) in alternatives {
+                    fmt_vec(f, guards, " | ", "", ", ")?;
+                    write!(f, " -> {}", e)?;
+                }
+                Ok(())
+            }
+            Rhs::Where(decls, rhs) => {
+                write!(f, "{} where ", rhs)?;
+                fmt_vec(f, decls, "{ ", " }", "; ")
+            }
+        }
+    }
+}
+
+impl Display for Guard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Guard::Bool(e) => write!(f, "{}", e),
+            Guard::Pattern(p, e) => write!(f, "{} <- {}", p, e),
         }
     }
 }
@@ -91,6 +193,21 @@ impl Display for Pattern {
             Pattern::Tuple(ps) => fmt_vec(f, ps, "(", ")", ", "),
             Pattern::FakeTuple(ps) => fmt_vec(f, ps, "", "", " "),
             Pattern::List(p1, p2) => write!(f, "({}:{})", p1, p2),
+            Pattern::As(name, p) => write!(f, "{}@{}", name, p),
+            Pattern::Bang(p) => write!(f, "!{}", p),
+            Pattern::Lazy(p) => write!(f, "~{}", p),
+            Pattern::Constructor(name, ps) if ps.is_empty() => write!(f, "{}", name),
+            Pattern::Constructor(name, ps) => fmt_vec(f, ps, &format!("({} ", name), ")", " "),
+        }
+    }
+}
+
+impl Display for InfixOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            InfixOp::Builtin(op) => write!(f, "{}", op),
+            InfixOp::Named(name) if name.starts_with(char::is_alphabetic) => write!(f, "`{}`", name),
+            InfixOp::Named(name) => write!(f, "{}", name),
         }
     }
 }
@@ -150,3 +267,78 @@ where
         }
     }
 }
+
+fn fmt_context(context: &[Constraint]) -> String {
+    match context {
+        [] => String::new(),
+        [(class, var)] => format!("{} {} => ", class, var),
+        constraints => {
+            let constraints = constraints
+                .iter()
+                .map(|(class, var)| format!("{} {}", class, var))
+                .collect::<Vec<_>>();
+            format!("({}) => ", constraints.join(", "))
+        }
+    }
+}
+
+/// Shows a double as Haskell does: the shortest digits that read back as the same number, in
+/// positional notation from `0.1` up to `10^7` and in scientific notation otherwise
+fn show_double(x: f64) -> String {
+    if x.is_nan() {
+        return "NaN".to_string();
+    }
+    let sign = if x.is_sign_negative() { "-" } else { "" };
+    if x.is_infinite() {
+        return format!("{}Infinity", sign);
+    }
+    // `{:e}` gives the shortest digits `d.ddd` and the exponent of the leading digit
+    let scientific = format!("{:e}", x.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let exponent: i32 = exponent.parse().unwrap();
+    if !(-1..7).contains(&exponent) {
+        let (first, rest) = digits.split_at(1);
+        let rest = if rest.is_empty() { "0" } else { rest };
+        return format!("{}{}.{}e{}", sign, first, rest, exponent);
+    }
+    if exponent == -1 {
+        return format!("{}0.{}", sign, digits);
+    }
+    let point = exponent as usize + 1;
+    let digits = format!("{:0<width$}", digits, width = point);
+    let (whole, fraction) = digits.split_at(point);
+    let fraction = if fraction.is_empty() { "0" } else { fraction };
+    format!("{}{}.{}", sign, whole, fraction)
+}
+
+/// The names of the ASCII control characters and space in escapes like `\SOH`, by code
+pub const ASCII_NAMES: [&str; 33] = [
+    "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT", "LF", "VT", "FF", "CR",
+    "SO", "SI", "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC",
+    "FS", "GS", "RS", "US", "SP",
+];
+
+/// Escapes a character of a literal as Haskell's `show` does. The character after it is needed
+/// to end a numeric escape or `\SO` with `\&` where the next character would extend it.
+fn escape(c: char, next: Option<char>) -> String {
+    let escaped = match c {
+        '\\' => "\\\\".to_string(),
+        '\x07' => "\\a".to_string(),
+        '\x08' => "\\b".to_string(),
+        '\t' => "\\t".to_string(),
+        '\n' => "\\n".to_string(),
+        '\x0b' => "\\v".to_string(),
+        '\x0c' => "\\f".to_string(),
+        '\r' => "\\r".to_string(),
+        '\x7f' => "\\DEL".to_string(),
+        c if c < ' ' => format!("\\{}", ASCII_NAMES[c as usize]),
+        c if c > '\x7f' => format!("\\{}", c as u32),
+        c => return c.to_string(),
+    };
+    match (c, next) {
+        ('\x0e', Some('H')) => escaped + "\\&",
+        (c, Some(next)) if c > '\x7f' && next.is_ascii_digit() => escaped + "\\&",
+        _ => escaped,
+    }
+}
@@ -0,0 +1,234 @@
+use ast::ast::{Literal, Type};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+
+use crate::error::RunTimeError;
+use crate::eval::RTResult;
+use crate::value::Value;
+
+/// What arithmetic on `Int` does when its result does not fit
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Overflow {
+    /// Fail with a runtime error
+    #[default]
+    Checked,
+    /// Wrap around, as GHC does
+    Wrapping,
+}
+
+impl Overflow {
+    fn int(self, checked: Option<i64>, wrapping: i64) -> RTResult<i64> {
+        match self {
+            Overflow::Checked => checked.ok_or(RunTimeError::IntOverflow),
+            Overflow::Wrapping => Ok(wrapping),
+        }
+    }
+}
+
+/// The number of arguments a primitive of the prelude takes, if `name` is one
+pub(crate) fn arity(name: &str) -> Option<usize> {
+    match name {
+        "primIntShow" | "primBoolShow" | "primCharShow" | "primStringShow" => Some(1),
+        "primIntegerShow" | "primDoubleShow" => Some(1),
+        "primIntNegate" | "primIntAbs" | "primIntSignum" => Some(1),
+        "primIntegerNegate" | "primIntegerAbs" | "primIntegerSignum" => Some(1),
+        "primIntFromInteger" | "primIntToInteger" | "primIntegerExponent" => Some(1),
+        "primDoubleNegate" | "primDoubleAbs" | "primDoubleSignum" => Some(1),
+        "primDoubleFromInteger" | "primDoubleTruncate" | "primDoubleRound" => Some(1),
+        "primDoubleFloor" | "primDoubleCeiling" => Some(1),
+        "primDoubleExp" | "primDoubleLog" | "primDoubleSqrt" => Some(1),
+        "primDoubleSin" | "primDoubleCos" | "primDoubleTan" => Some(1),
+        "primDoubleAsin" | "primDoubleAcos" | "primDoubleAtan" => Some(1),
+        "primIntEq" | "primBoolEq" | "primCharEq" => Some(2),
+        "primIntLt" | "primBoolLt" | "primCharLt" => Some(2),
+        "primIntegerEq" | "primIntegerLt" | "primDoubleEq" | "primDoubleLt" => Some(2),
+        "primDoubleLe" | "primDoubleGt" | "primDoubleGe" => Some(2),
+        "primIntAdd" | "primIntSub" | "primIntMul" => Some(2),
+        "primIntegerAdd" | "primIntegerSub" | "primIntegerMul" => Some(2),
+        "primIntDiv" | "primIntMod" | "primIntQuot" | "primIntRem" => Some(2),
+        "primIntegerDiv" | "primIntegerMod" | "primIntegerQuot" | "primIntegerRem" => Some(2),
+        "primDoubleAdd" | "primDoubleSub" | "primDoubleMul" | "primDoubleDiv" => Some(2),
+        "primDoublePow" => Some(2),
+        "primGetLine" | "primGetContents" => Some(0),
+        "primReturn" | "primPutStr" => Some(1),
+        "primBind" | "primSeq" => Some(2),
+        _ => None,
+    }
+}
+
+/// Whether the primitive `name` builds an IO action rather than computing a value
+pub(crate) fn is_action(name: &str) -> bool {
+    matches!(
+        name,
+        "primReturn" | "primBind" | "primPutStr" | "primGetLine" | "primGetContents"
+    )
+}
+
+/// Applies a primitive to its arguments, which have already been evaluated
+pub(crate) fn apply(name: &str, args: Vec<Value>, overflow: Overflow) -> RTResult<Value> {
+    let literals = args
+        .into_iter()
+        .map(|arg| match arg {
+            Value::Literal(l) => Ok(l),
+            _ => Err(RunTimeError::TypeError(
+                Type::TypeVariable("Literal".to_string()),
+                Type::TypeVariable("Later".to_string()),
+            )),
+        })
+        .collect::<RTResult<Vec<_>>>()?;
+    let result = match (name, &literals[..]) {
+        (_, [l]) if name.ends_with("Show") => Literal::String(l.to_string()),
+        (_, [l1, l2]) if name.ends_with("Eq") => Literal::Bool(l1 == l2),
+        (_, [l1, l2]) if name.ends_with("Lt") => Literal::Bool(less_than(l1, l2)),
+        ("primDoubleLe", [Literal::Double(x), Literal::Double(y)]) => Literal::Bool(x <= y),
+        ("primDoubleGt", [Literal::Double(x), Literal::Double(y)]) => Literal::Bool(x > y),
+        ("primDoubleGe", [Literal::Double(x), Literal::Double(y)]) => Literal::Bool(x >= y),
+        ("primIntAdd", [Literal::Int(x), Literal::Int(y)]) => {
+            Literal::Int(overflow.int(x.checked_add(*y), x.wrapping_add(*y))?)
+        }
+        ("primIntSub", [Literal::Int(x), Literal::Int(y)]) => {
+            Literal::Int(overflow.int(x.checked_sub(*y), x.wrapping_sub(*y))?)
+        }
+        ("primIntMul", [Literal::Int(x), Literal::Int(y)]) => {
+            Literal::Int(overflow.int(x.checked_mul(*y), x.wrapping_mul(*y))?)
+        }
+        ("primIntNegate", [Literal::Int(x)]) => {
+            Literal::Int(overflow.int(x.checked_neg(), x.wrapping_neg())?)
+        }
+        ("primIntAbs", [Literal::Int(x)]) => {
+            Literal::Int(overflow.int(x.checked_abs(), x.wrapping_abs())?)
+        }
+        ("primIntSignum", [Literal::Int(x)]) => Literal::Int(x.signum()),
+        ("primIntDiv", [Literal::Int(x), Literal::Int(y)]) => Literal::Int(div(*x, *y, overflow)?),
+        ("primIntMod", [Literal::Int(x), Literal::Int(y)]) => Literal::Int(modulo(*x, *y)?),
+        ("primIntQuot", [Literal::Int(x), Literal::Int(y)]) => {
+            Literal::Int(quot(*x, *y, overflow)?)
+        }
+        ("primIntRem", [Literal::Int(x), Literal::Int(y)]) => Literal::Int(rem(*x, *y)?),
+        ("primIntFromInteger", [Literal::Integer(x)]) => {
+            Literal::Int(overflow.int(x.to_i64(), truncate(x))?)
+        }
+        ("primIntToInteger", [Literal::Int(x)]) => Literal::Integer(BigInt::from(*x)),
+        ("primIntegerAdd", [Literal::Integer(x), Literal::Integer(y)]) => Literal::Integer(x + y),
+        ("primIntegerSub", [Literal::Integer(x), Literal::Integer(y)]) => Literal::Integer(x - y),
+        ("primIntegerMul", [Literal::Integer(x), Literal::Integer(y)]) => Literal::Integer(x * y),
+        ("primIntegerNegate", [Literal::Integer(x)]) => Literal::Integer(-x),
+        ("primIntegerAbs", [Literal::Integer(x)]) => Literal::Integer(x.abs()),
+        ("primIntegerSignum", [Literal::Integer(x)]) => Literal::Integer(x.signum()),
+        ("primIntegerDiv", [Literal::Integer(x), Literal::Integer(y)]) => {
+            Literal::Integer(nonzero(y).map(|y| x.div_floor(y))?)
+        }
+        ("primIntegerMod", [Literal::Integer(x), Literal::Integer(y)]) => {
+            Literal::Integer(nonzero(y).map(|y| x.mod_floor(y))?)
+        }
+        ("primIntegerQuot", [Literal::Integer(x), Literal::Integer(y)]) => {
+            Literal::Integer(nonzero(y).map(|y| x / y)?)
+        }
+        ("primIntegerRem", [Literal::Integer(x), Literal::Integer(y)]) => {
+            Literal::Integer(nonzero(y).map(|y| x % y)?)
+        }
+        ("primIntegerExponent", [Literal::Integer(x)]) if x.is_negative() => {
+            return Err(RunTimeError::NegativeExponent)
+        }
+        ("primIntegerExponent", [l @ Literal::Integer(_)]) => l.clone(),
+        ("primDoubleAdd", [Literal::Double(x), Literal::Double(y)]) => Literal::Double(x + y),
+        ("primDoubleSub", [Literal::Double(x), Literal::Double(y)]) => Literal::Double(x - y),
+        ("primDoubleMul", [Literal::Double(x), Literal::Double(y)]) => Literal::Double(x * y),
+        ("primDoubleDiv", [Literal::Double(x), Literal::Double(y)]) => Literal::Double(x / y),
+        ("primDoublePow", [Literal::Double(x), Literal::Double(y)]) => Literal::Double(x.powf(*y)),
+        ("primDoubleNegate", [Literal::Double(x)]) => Literal::Double(-x),
+        ("primDoubleAbs", [Literal::Double(x)]) => Literal::Double(x.abs()),
+        // Unlike `f64::signum`, the sign of zero is zero
+        ("primDoubleSignum", [Literal::Double(x)]) if *x == 0.0 || x.is_nan() => {
+            Literal::Double(*x)
+        }
+        ("primDoubleSignum", [Literal::Double(x)]) => Literal::Double(x.signum()),
+        ("primDoubleFromInteger", [Literal::Integer(x)]) => {
+            Literal::Double(x.to_f64().unwrap_or(f64::NAN))
+        }
+        ("primDoubleTruncate", [Literal::Double(x)]) => Literal::Integer(integer(x.trunc())?),
+        // Halfway cases round to the even integer, as in Haskell
+        ("primDoubleRound", [Literal::Double(x)]) => {
+            Literal::Integer(integer(x.round_ties_even())?)
+        }
+        ("primDoubleFloor", [Literal::Double(x)]) => Literal::Integer(integer(x.floor())?),
+        ("primDoubleCeiling", [Literal::Double(x)]) => Literal::Integer(integer(x.ceil())?),
+        ("primDoubleExp", [Literal::Double(x)]) => Literal::Double(x.exp()),
+        ("primDoubleLog", [Literal::Double(x)]) => Literal::Double(x.ln()),
+        ("primDoubleSqrt", [Literal::Double(x)]) => Literal::Double(x.sqrt()),
+        ("primDoubleSin", [Literal::Double(x)]) => Literal::Double(x.sin()),
+        ("primDoubleCos", [Literal::Double(x)]) => Literal::Double(x.cos()),
+        ("primDoubleTan", [Literal::Double(x)]) => Literal::Double(x.tan()),
+        ("primDoubleAsin", [Literal::Double(x)]) => Literal::Double(x.asin()),
+        ("primDoubleAcos", [Literal::Double(x)]) => Literal::Double(x.acos()),
+        ("primDoubleAtan", [Literal::Double(x)]) => Literal::Double(x.atan()),
+        _ => return Err(RunTimeError::VariableNotFound(name.to_string())),
+    };
+    Ok(Value::Literal(result))
+}
+
+fn less_than(l1: &Literal, l2: &Literal) -> bool {
+    match (l1, l2) {
+        (Literal::Int(x), Literal::Int(y)) => x < y,
+        (Literal::Integer(x), Literal::Integer(y)) => x < y,
+        (Literal::Double(x), Literal::Double(y)) => x < y,
+        (Literal::Bool(x), Literal::Bool(y)) => x < y,
+        (Literal::Char(x), Literal::Char(y)) => x < y,
+        (Literal::String(x), Literal::String(y)) => x < y,
+        _ => false,
+    }
+}
+
+/// The lowest 64 bits of an Integer, as `fromInteger` keeps of an Integer too large for `Int`
+fn truncate(x: &BigInt) -> i64 {
+    let modulus = BigInt::from(1) << 64;
+    x.mod_floor(&modulus).to_u64().unwrap_or_default() as i64
+}
+
+/// The integer a whole Double stands for, which NaN and the infinities have none of
+fn integer(x: f64) -> RTResult<BigInt> {
+    BigInt::from_f64(x).ok_or(RunTimeError::NonFiniteDouble)
+}
+
+fn nonzero(y: &BigInt) -> RTResult<&BigInt> {
+    match y.is_zero() {
+        true => Err(RunTimeError::DivisionByZero),
+        false => Ok(y),
+    }
+}
+
+/// Integer division rounding towards negative infinity, as `div` does
+pub(crate) fn div(x: i64, y: i64, overflow: Overflow) -> RTResult<i64> {
+    let (q, r) = (quot(x, y, overflow)?, rem(x, y)?);
+    match r != 0 && (r < 0) != (y < 0) {
+        true => Ok(q - 1),
+        false => Ok(q),
+    }
+}
+
+/// The remainder of `div`, which has the sign of the divisor
+pub(crate) fn modulo(x: i64, y: i64) -> RTResult<i64> {
+    let r = rem(x, y)?;
+    match r != 0 && (r < 0) != (y < 0) {
+        true => Ok(r + y),
+        false => Ok(r),
+    }
+}
+
+/// Integer division rounding towards zero, as `quot` does. Dividing the smallest Int by -1 is
+/// the only division that overflows.
+fn quot(x: i64, y: i64, overflow: Overflow) -> RTResult<i64> {
+    match y {
+        0 => Err(RunTimeError::DivisionByZero),
+        y => overflow.int(x.checked_div(y), x.wrapping_div(y)),
+    }
+}
+
+/// The remainder of `quot`, which has the sign of the dividend
+fn rem(x: i64, y: i64) -> RTResult<i64> {
+    match y {
+        0 => Err(RunTimeError::DivisionByZero),
+        y => Ok(x.wrapping_rem(y)),
+    }
+}
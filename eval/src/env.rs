@@ -1,17 +1,33 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Formatter};
 use std::rc::Rc;
 
+use ast::ast::Expr;
+
+use crate::builtins::{self, Overflow};
 use crate::error::RunTimeError;
 use crate::value::Value;
 
 type RTResult<T> = Result<T, RunTimeError>;
 
+type Group = Rc<RefCell<HashMap<String, Value>>>;
+
+#[derive(Clone)]
+enum Binding {
+    /// A variable, whose value is replaced by its evaluated form once it has been forced, in
+    /// every environment sharing it
+    Value(Rc<RefCell<Value>>),
+    /// A member of a group of local bindings that may refer to each other, which all share the
+    /// table holding their values
+    Recursive(Group),
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct Env {
     functions: Rc<RefCell<HashMap<String, Value>>>,
-    env: HashMap<String, Rc<Value>>,
+    env: HashMap<String, Binding>,
+    pub overflow: Overflow,
 }
 
 impl Env {
@@ -19,6 +35,7 @@ impl Env {
         Env {
             functions: Rc::new(RefCell::new(HashMap::new())),
             env: HashMap::new(),
+            overflow: Overflow::default(),
         }
     }
     pub fn add_function(&mut self, name: String, v: Value) {
@@ -28,8 +45,47 @@ impl Env {
     pub fn extended(&self, name: String, val: Value) -> Env {
         let functions = self.functions.clone();
         let mut env = self.env.clone();
-        env.insert(name, Rc::new(val));
-        Env { functions, env }
+        env.insert(name, Binding::Value(Rc::new(RefCell::new(val))));
+        Env {
+            functions,
+            env,
+            overflow: self.overflow,
+        }
+    }
+
+    /// Extends the environment with a group of bindings, each of which is a closure over the
+    /// extended environment so that they can refer to each other.
+    pub fn recursive(&self, bindings: Vec<(String, Vec<String>, Expr)>) -> Env {
+        let group: Group = Rc::new(RefCell::new(HashMap::new()));
+        let mut env = self.env.clone();
+        for (name, _, _) in &bindings {
+            env.insert(name.clone(), Binding::Recursive(group.clone()));
+        }
+        let extended = Env {
+            functions: self.functions.clone(),
+            env,
+            overflow: self.overflow,
+        };
+        for (name, args, e) in bindings {
+            let closure = Value::Closure(e, args, extended.clone());
+            (*group).borrow_mut().insert(name, closure);
+        }
+        extended
+    }
+
+    /// The environment with only the binding of `name`, shared with this one, so that a closure
+    /// over a single variable does not keep the rest of the environment alive
+    pub fn only(&self, name: &String) -> Env {
+        Env {
+            functions: self.functions.clone(),
+            env: self
+                .env
+                .get_key_value(name)
+                .map(|(name, binding)| (name.clone(), binding.clone()))
+                .into_iter()
+                .collect(),
+            overflow: self.overflow,
+        }
     }
 
     pub fn contains(&self, name: &String) -> bool {
@@ -39,16 +95,38 @@ impl Env {
     pub fn get(&self, name: &String) -> RTResult<Value> {
         let var = self.env.get(name);
         match var {
-            Some(val) => Ok((**val).clone()),
+            Some(Binding::Value(val)) => Ok((**val).borrow().clone()),
+            Some(Binding::Recursive(group)) => match (*group).borrow().get(name) {
+                Some(val) => Ok(val.clone()),
+                None => Err(RunTimeError::VariableNotFound(name.clone())),
+            },
             None => match (*self.functions).borrow().get(name) {
                 Some(val) => Ok(val.clone()),
+                None if builtins::arity(name) == Some(0) => Ok(Value::Action(name.clone(), vec![])),
+                None if builtins::arity(name).is_some() => Ok(Value::Builtin(name.clone(), vec![])),
                 None => Err(RunTimeError::VariableNotFound(name.clone())),
             },
         }
     }
-    pub fn update_value(&mut self, name: &String, val: Value) {
-        if self.env.contains_key(name) {
-            self.env.insert(name.to_string(), Rc::new(val));
+    pub fn update_value(&self, name: &String, val: Value) {
+        match self.env.get(name) {
+            Some(Binding::Value(cell)) => {
+                *(**cell).borrow_mut() = val;
+            }
+            Some(Binding::Recursive(group)) => {
+                (*group).borrow_mut().insert(name.to_string(), val);
+            }
+            None => {}
+        }
+    }
+}
+
+impl Debug for Binding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Value(val) => write!(f, "{:?}", (**val).borrow()),
+            // The values of a group hold environments containing the group itself
+            Binding::Recursive(group) => write!(f, "Recursive{:?}", (*group).borrow().keys()),
         }
     }
 }
@@ -13,4 +13,18 @@ pub enum RunTimeError {
     EndOfProgram,
     #[error("Found non Exhaustive pattern in function")]
     NonExhaustivePattern,
+    #[error("Division by zero")]
+    DivisionByZero,
+    #[error("Negative exponent")]
+    NegativeExponent,
+    #[error("Arithmetic overflow on Int")]
+    IntOverflow,
+    #[error("Cannot convert NaN or Infinity to an integer")]
+    NonFiniteDouble,
+    #[error("The program has no main action to run")]
+    MissingMain,
+    #[error("Reached the end of the input")]
+    EndOfInput,
+    #[error("Input or output failed: {0}")]
+    Io(#[from] std::io::Error),
 }
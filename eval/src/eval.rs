@@ -1,28 +1,120 @@
+use std::io::{BufRead, Write};
 use std::iter::zip;
 
+use crate::builtins::{self, Overflow};
 use crate::env::Env;
 use crate::value::Value;
 use ast::ast::Decl::*;
 use ast::ast::Op::*;
-use ast::ast::{Decl, Expr, List, Literal, Pattern, Type};
+use ast::ast::{Decl, Expr, Guard, List, Literal, Pattern, Rhs, Type};
 use log::info;
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive};
 
 use crate::error::RunTimeError;
 
 pub type RTResult<T> = Result<T, RunTimeError>;
 
-pub fn eval(program: Vec<Decl>) -> RTResult<()> {
+/// The name the value a lazy pattern takes apart is kept under
+const LAZY_VALUE: &str = "lazy:value";
+
+pub fn eval(program: Vec<Decl>, overflow: Overflow) -> RTResult<()> {
     let mut env = Env::new();
+    env.overflow = overflow;
     for decl in program {
         eval_decl(&mut env, decl)?;
     }
     Ok(())
 }
 
+/// Evaluates the declarations of a program and runs its `main` action. Top-level expressions are
+/// not echoed.
+pub fn run_main(
+    program: Vec<Decl>,
+    overflow: Overflow,
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+) -> RTResult<()> {
+    let mut env = Env::new();
+    env.overflow = overflow;
+    for decl in program {
+        if !matches!(decl, SExpr(_)) {
+            eval_decl(&mut env, decl)?;
+        }
+    }
+    let main = "main".to_string();
+    if !env.contains(&main) {
+        return Err(RunTimeError::MissingMain);
+    }
+    let action = env.get(&main)?;
+    run_action(&env, action, input, output)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// Performs the effects of an IO action and returns its result
+fn run_action(
+    env: &Env,
+    action: Value,
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+) -> RTResult<Value> {
+    let mut action = action;
+    // The continuation of a bind is run in this loop rather than recursively, so that actions
+    // looping forever run in constant stack space
+    loop {
+        let Value::Action(name, args) = handle_closure(action)? else {
+            return Err(RunTimeError::TypeError(
+                Type::TypeVariable("IO".to_string()),
+                Type::TypeVariable("Later".to_string()),
+            ));
+        };
+        let mut args = args.into_iter();
+        match (name.as_str(), args.next(), args.next()) {
+            ("primReturn", Some(x), None) => return Ok(x),
+            ("primBind", Some(m), Some(k)) => {
+                let x = run_action(env, m, input, output)?;
+                let (k_name, x_name) = ("io:k".to_string(), "io:x".to_string());
+                let call = Expr::Application(
+                    Box::new(Expr::Var(k_name.clone())),
+                    Box::new(Expr::Var(x_name.clone())),
+                );
+                let mut call_env = env.extended(k_name, k).extended(x_name, x);
+                action = eval_expr(&mut call_env, call)?;
+            }
+            ("primPutStr", Some(s), None) => {
+                write!(output, "{}", force_string(s)?)?;
+                return Ok(Value::Tuple(vec![]));
+            }
+            ("primGetLine", None, None) => {
+                // Prompts written before reading have to be seen
+                output.flush()?;
+                let mut line = String::new();
+                if input.read_line(&mut line)? == 0 {
+                    return Err(RunTimeError::EndOfInput);
+                }
+                if line.ends_with('\n') {
+                    line.pop();
+                }
+                return Ok(Value::Literal(Literal::String(line)));
+            }
+            ("primGetContents", None, None) => {
+                output.flush()?;
+                let mut contents = String::new();
+                input.read_to_string(&mut contents)?;
+                return Ok(Value::Literal(Literal::String(contents)));
+            }
+            _ => return Err(RunTimeError::VariableNotFound(name)),
+        }
+    }
+}
+
 fn eval_decl(env: &mut Env, decl: Decl) -> RTResult<()> {
     match decl {
         TypeAlias(_, _) => Ok(()),
-        TypeSignature(_, _) => Ok(()),
+        TypeSignature(_, _, _) => Ok(()),
+        DataDecl(_, _, _) => Ok(()),
+        ClassDecl(_, _, _, _) | InstanceDecl(_, _, _, _) => Ok(()),
         FunDecl(name, args, e) => {
             let closure = Value::Closure(e, args, env.clone());
             info!("Storing {}: {} to env", name, closure);
@@ -57,11 +149,31 @@ fn force_eval(v: Value) -> RTResult<Value> {
             Box::new(force_eval(*x)?),
             Box::new(force_eval(*xs)?),
         )),
+        Value::Constructor(name, vs) => Ok(Value::Constructor(
+            name,
+            vs.into_iter()
+                .map(force_eval)
+                .collect::<RTResult<Vec<_>>>()?,
+        )),
         x => Ok(x),
     }
 }
 
+/// Evaluates an expression. Expressions in tail position come back as closures without
+/// arguments, which are evaluated in this loop rather than recursively, so that tail calls run in
+/// constant stack space.
 fn eval_expr(env: &mut Env, expr: Expr) -> RTResult<Value> {
+    let mut value = eval_step(env, expr)?;
+    while let Value::Closure(e, args, mut c_env) = value {
+        if !args.is_empty() {
+            return Ok(Value::Closure(e, args, c_env));
+        }
+        value = eval_step(&mut c_env, e)?;
+    }
+    Ok(value)
+}
+
+fn eval_step(env: &mut Env, expr: Expr) -> RTResult<Value> {
     info!("Interpreting Expression {} with env:", expr);
     info!("{env:?}");
     match expr {
@@ -72,23 +184,21 @@ fn eval_expr(env: &mut Env, expr: Expr) -> RTResult<Value> {
             Ok(v)
         }
         Expr::Literal(l) => Ok(Value::Literal(l)),
+        Expr::Constructor(name) => Ok(Value::Constructor(name, vec![])),
         Expr::Tuple(es) => Ok(Value::Tuple(
-            es.into_iter()
-                .map(|e| Value::Closure(e, vec![], env.clone()))
-                .collect(),
+            es.into_iter().map(|e| delay(env, e)).collect(),
         )),
         Expr::If(test, ethen, eelse) => {
             let tv = eval_bool(env, *test)?;
-            if tv {
-                return eval_expr(env, *ethen);
-            }
-            return eval_expr(env, *eelse);
+            let branch = if tv { *ethen } else { *eelse };
+            Ok(Value::Closure(branch, vec![], env.clone()))
         }
         Expr::Lambda(var_name, expr) => Ok(Value::Closure(*expr, vec![var_name], env.clone())),
-        Expr::Let(var_name, expr1, expr2) => {
-            let var = Value::Closure(*expr1, vec![], env.clone());
-            eval_expr(&mut env.extended(var_name, var), *expr2)
-        }
+        Expr::Let(decls, expr) => Ok(Value::Closure(
+            *expr,
+            vec![],
+            env.recursive(bindings(decls)),
+        )),
         Expr::BinOp(l, op, r) => {
             return match op {
                 Add => {
@@ -109,21 +219,25 @@ fn eval_expr(env: &mut Env, expr: Expr) -> RTResult<Value> {
                 Div => {
                     let lv = eval_int(env, *l)?;
                     let rv = eval_int(env, *r)?;
-                    Ok(Value::Literal(Literal::Int(lv / rv)))
+                    Ok(Value::Literal(Literal::Int(builtins::div(
+                        lv,
+                        rv,
+                        env.overflow,
+                    )?)))
                 }
                 Mod => {
                     let lv = eval_int(env, *l)?;
                     let rv = eval_int(env, *r)?;
-                    Ok(Value::Literal(Literal::Int(lv % rv)))
+                    Ok(Value::Literal(Literal::Int(builtins::modulo(lv, rv)?)))
                 }
                 Eq => {
-                    let lv = eval_expr(env, *l)?;
-                    let rv = eval_expr(env, *r)?;
+                    let lv = force_eval(eval_expr(env, *l)?)?;
+                    let rv = force_eval(eval_expr(env, *r)?)?;
                     Ok(Value::Literal(Literal::Bool(lv == rv)))
                 }
                 Neq => {
-                    let lv = eval_expr(env, *l)?;
-                    let rv = eval_expr(env, *r)?;
+                    let lv = force_eval(eval_expr(env, *l)?)?;
+                    let rv = force_eval(eval_expr(env, *r)?)?;
                     Ok(Value::Literal(Literal::Bool(lv != rv)))
                 }
                 Lt => {
@@ -158,7 +272,6 @@ fn eval_expr(env: &mut Env, expr: Expr) -> RTResult<Value> {
                 }
                 Append => {
                     let lv = eval_expr(env, *l)?;
-                    let rv = eval_expr(env, *r)?;
                     let mut curr = lv;
                     let mut elements = vec![];
                     while let Value::List(x, xs) = curr {
@@ -168,6 +281,17 @@ fn eval_expr(env: &mut Env, expr: Expr) -> RTResult<Value> {
                             tail => curr = tail,
                         }
                     }
+                    if let Value::Literal(Literal::String(s)) = curr {
+                        let chars = s
+                            .chars()
+                            .map(|c| Box::new(Value::Literal(Literal::Char(c))));
+                        elements.extend(chars);
+                    }
+                    if elements.is_empty() {
+                        return eval_expr(env, *r);
+                    }
+                    // The right list is only evaluated once the left one has been consumed
+                    let rv = Value::Closure(*r, vec![], env.clone());
                     let big_list = elements
                         .into_iter()
                         .rev()
@@ -180,26 +304,64 @@ fn eval_expr(env: &mut Env, expr: Expr) -> RTResult<Value> {
                 )),
             };
         }
+        Expr::Operator(op) => {
+            let (left, right) = ("op:left".to_string(), "op:right".to_string());
+            let body = Expr::BinOp(
+                Box::new(Expr::Var(left.clone())),
+                op,
+                Box::new(Expr::Var(right.clone())),
+            );
+            Ok(Value::Closure(body, vec![right, left], env.clone()))
+        }
+        Expr::Infix(_, _) | Expr::Negate(_) => {
+            unreachable!("Infix expressions are resolved by the parser")
+        }
+        // The typechecker erases annotations, but a program may be evaluated without it
+        Expr::Annotated(e, _) => eval_expr(env, *e),
         Expr::Application(f, e) => {
-            let e_closure = Value::Closure(*e, vec![], env.clone());
+            let e_closure = delay(env, *e);
             match eval_expr(env, *f)? {
                 // Typechecker should ensure f is a function with arity > 0
                 Value::Closure(e, v, c_env) => {
                     let mut v = v;
                     let name = v.pop().unwrap();
                     info!("Pushing {}: {} to env", name, e);
-                    let mut new_env = c_env.extended(name, e_closure);
-                    match &v[..] {
-                        [] => eval_expr(&mut new_env, e),
-                        _ => Ok(Value::Closure(e, v, new_env)),
+                    // Once all arguments are there, the body is evaluated as a tail call
+                    Ok(Value::Closure(e, v, c_env.extended(name, e_closure)))
+                }
+                Value::Constructor(name, mut args) => {
+                    args.push(e_closure);
+                    Ok(Value::Constructor(name, args))
+                }
+                Value::Builtin(name, mut args) => {
+                    args.push(e_closure);
+                    if Some(args.len()) != builtins::arity(&name) {
+                        return Ok(Value::Builtin(name, args));
+                    }
+                    if builtins::is_action(&name) {
+                        return Ok(Value::Action(name, args));
                     }
+                    if name == "primSeq" {
+                        return seq(args);
+                    }
+                    // The only lists primitives take are strings, which they take packed
+                    let args = args
+                        .into_iter()
+                        .map(|arg| match handle_closure(arg)? {
+                            v @ (Value::List(_, _) | Value::EmptyList) => {
+                                Ok(Value::Literal(Literal::String(force_string(v)?)))
+                            }
+                            v => Ok(v),
+                        })
+                        .collect::<RTResult<Vec<_>>>()?;
+                    builtins::apply(&name, args, env.overflow)
                 }
                 _ => unreachable!(),
             }
         }
         Expr::Case(e, cases) => {
-            let (body, mut new_env) = pattern_match_expr(env, *e, &cases)?;
-            eval_expr(&mut new_env, body)
+            let (body, new_env) = pattern_match_expr(env, *e, &cases)?;
+            Ok(Value::Closure(body, vec![], new_env))
         }
         Expr::List(ls) => match ls {
             List::Some(head, tail) => Ok(Value::List(
@@ -208,27 +370,42 @@ fn eval_expr(env: &mut Env, expr: Expr) -> RTResult<Value> {
             )),
             List::Empty => Ok(Value::EmptyList),
         },
-        Expr::Range(start, step, stop) => {
-            let start = eval_int(env, *start)?;
-            let step = eval_int(env, *step)?;
-            let stop = stop.map(|stop| eval_int(env, *stop)).transpose()?;
-            if let Some(stop) = stop {
-                if start > stop {
-                    return Ok(Value::EmptyList);
-                }
+        Expr::Range(from, then, to) => {
+            let from = eval_enum(env, *from)?;
+            let then = then.map(|then| eval_enum(env, *then)).transpose()?;
+            let to = to.map(|to| eval_enum(env, *to)).transpose()?;
+            let start = from_enum(&from);
+            let step = then
+                .as_ref()
+                .map(|then| from_enum(then) - &start)
+                .unwrap_or(BigInt::from(1));
+            let within = |n: &BigInt| match &to {
+                Some(to) if step.is_negative() => *n >= from_enum(to),
+                Some(to) => *n <= from_enum(to),
+                None => true,
+            };
+            if !within(&start) {
+                return Ok(Value::EmptyList);
             }
-            return Ok(Value::List(
-                Box::new(Value::Literal(Literal::Int(start))),
-                Box::new(Value::Closure(
-                    Expr::Range(
-                        Box::new(Expr::Literal(Literal::Int(start + step))),
-                        Box::new(Expr::Literal(Literal::Int(step))),
-                        stop.map(|x| Box::new(Expr::Literal(Literal::Int(x)))),
-                    ),
-                    vec![],
-                    env.clone(),
-                )),
-            ));
+            let literal = |l: Literal| Box::new(Expr::Literal(l));
+            let range = |from, then| {
+                let range = Expr::Range(literal(from), then, to.clone().map(literal));
+                Value::Closure(range, vec![], env.clone())
+            };
+            let next = to_enum(&from, &start + &step);
+            let tail = match (next, &then) {
+                (None, _) => Value::EmptyList,
+                (Some(next), None) => range(next, None),
+                (Some(next), Some(_)) => match to_enum(&from, &start + 2 * &step) {
+                    Some(next_then) => range(next, Some(literal(next_then))),
+                    // Past the element after this one the range leaves the type, so it ends there
+                    None if within(&(&start + &step)) => {
+                        Value::List(Box::new(Value::Literal(next)), Box::new(Value::EmptyList))
+                    }
+                    None => Value::EmptyList,
+                },
+            };
+            Ok(Value::List(Box::new(Value::Literal(from)), Box::new(tail)))
         }
     }
 }
@@ -246,6 +423,63 @@ fn handle_closure(v: Value) -> RTResult<Value> {
     }
 }
 
+/// Evaluates the first of the arguments of `seq` as far as its outermost constructor, and leaves
+/// the second to be evaluated as a tail call
+fn seq(args: Vec<Value>) -> RTResult<Value> {
+    let [x, y] = <[Value; 2]>::try_from(args).map_err(|_| RunTimeError::MissingArgument)?;
+    handle_closure(x)?;
+    Ok(y)
+}
+
+/// Delays the evaluation of an expression. A variable is passed on as it is bound when it has
+/// been evaluated already or only stands for another variable, so that variables passed down
+/// recursive calls, like class dictionaries, do not build chains of closures one call deep each.
+/// Otherwise it is delayed in an environment holding only itself, so that the closure does not
+/// keep the environments of all calls before alive.
+fn delay(env: &Env, e: Expr) -> Value {
+    if let Expr::Var(name) = &e {
+        match env.get(name) {
+            Ok(Value::Closure(body, args, _))
+                if args.is_empty() && !matches!(body, Expr::Var(_)) =>
+            {
+                let only = env.only(name);
+                return Value::Closure(e, vec![], only);
+            }
+            Ok(v) => return v,
+            Err(_) => {}
+        }
+    }
+    Value::Closure(e, vec![], env.clone())
+}
+
+/// Collects the characters of a string. Strings are lists of characters, but string literals are
+/// kept packed until they are taken apart, so the tail of a list may be packed too.
+fn force_string(v: Value) -> RTResult<String> {
+    let mut string = String::new();
+    let mut current = handle_closure(v)?;
+    loop {
+        match current {
+            Value::Literal(Literal::String(rest)) => {
+                string.push_str(&rest);
+                return Ok(string);
+            }
+            Value::EmptyList => return Ok(string),
+            Value::List(c, cs) => {
+                let Value::Literal(Literal::Char(c)) = handle_closure(*c)? else {
+                    break;
+                };
+                string.push(c);
+                current = handle_closure(*cs)?;
+            }
+            _ => break,
+        }
+    }
+    Err(RunTimeError::TypeError(
+        Type::String,
+        Type::TypeVariable("Later".to_string()),
+    ))
+}
+
 fn eval_int(env: &mut Env, expr: Expr) -> RTResult<i64> {
     info!("Evaluating {} to int", &expr);
     match eval_expr(env, expr)? {
@@ -256,6 +490,51 @@ fn eval_int(env: &mut Env, expr: Expr) -> RTResult<i64> {
         )),
     }
 }
+/// Evaluates an element of an arithmetic sequence, which is an Int, an Integer or a Char
+fn eval_enum(env: &mut Env, expr: Expr) -> RTResult<Literal> {
+    match eval_expr(env, expr)? {
+        Value::Literal(l @ (Literal::Int(_) | Literal::Integer(_) | Literal::Char(_))) => Ok(l),
+        _ => Err(RunTimeError::TypeError(
+            Type::TypeVariable("Enum".to_string()),
+            Type::TypeVariable("Later".to_string()),
+        )),
+    }
+}
+
+fn from_enum(l: &Literal) -> BigInt {
+    match l {
+        Literal::Char(c) => BigInt::from(*c as u32),
+        Literal::Int(n) => BigInt::from(*n),
+        Literal::Integer(n) => n.clone(),
+        _ => unreachable!(),
+    }
+}
+
+/// The `n`-th element of the enumeration `like` belongs to, if there is one
+fn to_enum(like: &Literal, n: BigInt) -> Option<Literal> {
+    match like {
+        Literal::Char(_) => u32::try_from(n)
+            .ok()
+            .and_then(char::from_u32)
+            .map(Literal::Char),
+        Literal::Int(_) => i64::try_from(n).ok().map(Literal::Int),
+        _ => Some(Literal::Integer(n)),
+    }
+}
+
+/// Whether a value matches a literal pattern. Integer literals are overloaded, so they match Ints,
+/// Integers and Doubles alike.
+fn literal_matches(pattern: &Literal, value: &Literal) -> bool {
+    match (pattern, value) {
+        (Literal::Int(n), Literal::Integer(m)) | (Literal::Integer(m), Literal::Int(n)) => {
+            BigInt::from(*n) == *m
+        }
+        (Literal::Int(n), Literal::Double(x)) => *n as f64 == *x,
+        (Literal::Integer(n), Literal::Double(x)) => n.to_f64() == Some(*x),
+        (l1, l2) => l1 == l2,
+    }
+}
+
 fn eval_bool(env: &mut Env, expr: Expr) -> RTResult<bool> {
     let v = handle_closure(eval_expr(env, expr)?)?;
     match v {
@@ -270,67 +549,135 @@ fn eval_bool(env: &mut Env, expr: Expr) -> RTResult<bool> {
 fn pattern_match_expr<'a>(
     env: &mut Env,
     e: Expr,
-    cases: &[(Pattern, Expr)],
+    cases: &[(Pattern, Rhs)],
 ) -> RTResult<(Expr, Env)> {
-    for (p, body) in cases {
-        if let Some(updated_env) = match_pattern(env, p, e.clone())? {
-            return Ok((body.clone(), updated_env));
+    for (p, rhs) in cases {
+        if let Some(mut updated_env) = match_pattern(env, p, e.clone())? {
+            // When no guard succeeds, matching falls through to the next alternative
+            if let Some(selected) = select_rhs(&mut updated_env, rhs)? {
+                return Ok(selected);
+            }
         }
     }
     Err(RunTimeError::NonExhaustivePattern)
 }
 
-fn match_pattern(env: &mut Env, p: &Pattern, e: Expr) -> RTResult<Option<Env>> {
-    info!("Matching pattern: {}", p);
-    match p {
-        Pattern::Var(name) => {
-            let new_env =
-                env.extended(name.clone(), Value::Closure(e.clone(), vec![], env.clone()));
-            return Ok(Some(new_env));
-        }
-        Pattern::Wildcard => {
-            return Ok(Some(env.clone()));
-        }
-        Pattern::EmptyList => {
-            let v = eval_expr(env, e.clone())?;
-            if let Value::EmptyList = v {
-                return Ok(Some(env.clone()));
-            }
-        }
-        Pattern::Literal(l1) => {
-            let v = eval_expr(env, e.clone())?;
-            if let Value::Literal(l2) = v {
-                if *l1 == l2 {
-                    return Ok(Some(env.clone()));
+/// Finds the expression a right-hand side evaluates to, if any of its guards succeed
+fn select_rhs(env: &mut Env, rhs: &Rhs) -> RTResult<Option<(Expr, Env)>> {
+    match rhs {
+        Rhs::Expr(e) => Ok(Some((e.clone(), env.clone()))),
+        Rhs::Guarded(alternatives) => {
+            'alternatives: for (guards, e) in alternatives {
+                let mut guard_env = env.clone();
+                for guard in guards {
+                    match guard {
+                        Guard::Bool(cond) => {
+                            if !eval_bool(&mut guard_env, cond.clone())? {
+                                continue 'alternatives;
+                            }
+                        }
+                        Guard::Pattern(p, e) => {
+                            match match_pattern(&mut guard_env, p, e.clone())? {
+                                Some(new_env) => guard_env = new_env,
+                                None => continue 'alternatives,
+                            }
+                        }
+                    }
                 }
+                return Ok(Some((e.clone(), guard_env)));
             }
+            Ok(None)
         }
-        Pattern::List(_, _) => {
-            let v = eval_expr(env, e)?;
-            return matches_value(env, p, &v);
+        Rhs::Where(decls, rhs) => {
+            let mut where_env = env.recursive(bindings(decls.clone()));
+            select_rhs(&mut where_env, rhs)
         }
-        Pattern::FakeTuple(ps) => {
-            return match_pattern(env, &Pattern::Tuple(ps.to_vec()), e);
+    }
+}
+
+/// The function bindings of a `let` or `where` block
+fn bindings(decls: Vec<Decl>) -> Vec<(String, Vec<String>, Expr)> {
+    decls
+        .into_iter()
+        .filter_map(|decl| match decl {
+            FunDecl(name, args, e) => Some((name, args, e)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn match_pattern(env: &mut Env, p: &Pattern, e: Expr) -> RTResult<Option<Env>> {
+    info!("Matching pattern: {}", p);
+    match p {
+        Pattern::Var(name) => Ok(Some(env.extended(name.clone(), delay(env, e)))),
+        Pattern::Wildcard => Ok(Some(env.clone())),
+        Pattern::EmptyList
+        | Pattern::Literal(_)
+        | Pattern::List(_, _)
+        | Pattern::Constructor(_, _)
+        | Pattern::As(_, _)
+        | Pattern::Bang(_) => {
+            let v = eval_expr(env, e)?;
+            matches_value(env, p, &v)
         }
+        Pattern::Lazy(_) => matches_value(env, p, &Value::Closure(e, vec![], env.clone())),
+        Pattern::FakeTuple(ps) => match_pattern(env, &Pattern::Tuple(ps.to_vec()), e),
         Pattern::Tuple(ps) => {
             let vs = eval_expr(env, e)?;
-            return matches_value(env, &Pattern::Tuple(ps.clone()), &vs);
+            matches_value(env, &Pattern::Tuple(ps.clone()), &vs)
         }
     }
-    Ok(None)
+}
+
+/// Binds each variable of a lazy pattern to a match of the value against the pattern, which only
+/// happens when the variable is demanded
+fn lazy_match(env: &Env, p: &Pattern, v: &Value) -> Env {
+    let scope = env.extended(LAZY_VALUE.to_string(), v.clone());
+    let mut new_env = env.clone();
+    for var in p.vars() {
+        let alternative = (p.clone(), Rhs::Expr(Expr::Var(var.clone())));
+        let select = Expr::Case(
+            Box::new(Expr::Var(LAZY_VALUE.to_string())),
+            vec![alternative],
+        );
+        new_env = new_env.extended(var, Value::Closure(select, vec![], scope.clone()));
+    }
+    new_env
 }
 
 fn matches_value(env: &mut Env, p: &Pattern, v: &Value) -> RTResult<Option<Env>> {
     info!("Matching val {} on pattern {}", v, p);
     Ok(match (p, v) {
         (Pattern::Wildcard, _) => Some(env.clone()),
-        (Pattern::Literal(l2), Value::Literal(l1)) => l1.eq(l2).then_some(env.clone()),
+        (Pattern::Literal(l2), Value::Literal(l1)) => {
+            literal_matches(l2, l1).then_some(env.clone())
+        }
         (Pattern::Var(name), v) => Some(env.extended(name.clone(), v.clone())),
+        (Pattern::As(name, p), v) => {
+            matches_value(env, p, v)?.map(|new_env| new_env.extended(name.clone(), v.clone()))
+        }
         (Pattern::EmptyList, Value::EmptyList) => Some(env.clone()),
         (Pattern::List(p1, p2), Value::List(v1, v2)) => match matches_value(env, p1, v1)? {
             Some(mut new_env) => matches_value(&mut new_env, p2, v2)?,
             None => None,
         },
+        // A packed string is only unpacked as far as a list pattern takes it apart
+        (Pattern::EmptyList, Value::Literal(Literal::String(s))) => {
+            s.is_empty().then_some(env.clone())
+        }
+        (Pattern::List(p1, p2), Value::Literal(Literal::String(s))) => {
+            let mut chars = s.chars();
+            let Some(c) = chars.next() else {
+                return Ok(None);
+            };
+            let rest = Value::Literal(Literal::String(chars.as_str().to_string()));
+            match matches_value(env, p1, &Value::Literal(Literal::Char(c)))? {
+                Some(mut new_env) => matches_value(&mut new_env, p2, &rest)?,
+                None => None,
+            }
+        }
+        (Pattern::Bang(p), v) => matches_value(env, p, &handle_closure(v.clone())?)?,
+        (Pattern::Lazy(p), v) => Some(lazy_match(env, p, v)),
         (p, Value::Closure(e, v, inner_env)) => {
             if v.len() != 0 {
                 return Ok(None);
@@ -350,7 +697,29 @@ fn matches_value(env: &mut Env, p: &Pattern, v: &Value) -> RTResult<Option<Env>>
             }
             Some(curr)
         }
+        (Pattern::Constructor(name, ps), Value::Constructor(con, vs)) => {
+            if name != con || ps.len() != vs.len() {
+                return Ok(None);
+            }
+            let mut curr = env.clone();
+            for (p, v) in zip(ps, vs) {
+                match matches_value(&mut curr, p, v)? {
+                    Some(new_env) => curr = new_env,
+                    None => {
+                        return Ok(None);
+                    }
+                }
+            }
+            Some(curr)
+        }
         (Pattern::FakeTuple(ps), _) => matches_value(env, &Pattern::Tuple(ps.to_vec()), v)?,
+        // A string literal matches a list of characters one character at a time
+        (Pattern::Literal(Literal::String(s)), Value::List(_, _) | Value::EmptyList) => {
+            let pattern = s.chars().rev().fold(Pattern::EmptyList, |acc, c| {
+                Pattern::List(Box::new(Pattern::Literal(Literal::Char(c))), Box::new(acc))
+            });
+            matches_value(env, &pattern, v)?
+        }
         (_, _) => None,
     })
 }
@@ -1,11 +1,27 @@
+use std::io::{BufRead, Write};
+
 use ast::ast::Decl;
 use eval::RTResult;
 
+mod builtins;
 mod env;
 mod error;
 mod eval;
 mod value;
 
+pub use builtins::Overflow;
+
+/// Evaluates a program, failing on arithmetic overflow on `Int`
 pub fn eval(program: Vec<Decl>) -> RTResult<()> {
-    eval::eval(program)
+    eval::eval(program, Overflow::Checked)
+}
+
+/// Evaluates a program with the given behaviour on arithmetic overflow on `Int`
+pub fn eval_with(program: Vec<Decl>, overflow: Overflow) -> RTResult<()> {
+    eval::eval(program, overflow)
+}
+
+/// Evaluates a program and runs its `main` action, which reads from `input` and writes to `output`
+pub fn run_main(program: Vec<Decl>, input: &mut dyn BufRead, output: &mut dyn Write) -> RTResult<()> {
+    eval::run_main(program, Overflow::Checked, input, output)
 }
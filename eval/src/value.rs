@@ -10,12 +10,23 @@ pub enum Value {
     Closure(Expr, Vec<String>, Env),
     List(Box<Value>, Box<Value>),
     EmptyList,
+    Constructor(String, Vec<Value>),
+    /// A primitive of the prelude with the arguments it has been applied to so far
+    Builtin(String, Vec<Value>),
+    /// An IO action built by a primitive, whose effects only happen once it is run
+    Action(String, Vec<Value>),
 }
 
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Value::Literal(l1), Value::Literal(l2)) => l1 == l2,
+            (Value::Tuple(vs1), Value::Tuple(vs2)) => vs1 == vs2,
+            (Value::List(x1, xs1), Value::List(x2, xs2)) => x1 == x2 && xs1 == xs2,
+            (Value::EmptyList, Value::EmptyList) => true,
+            (Value::Constructor(name1, vs1), Value::Constructor(name2, vs2)) => {
+                name1 == name2 && vs1 == vs2
+            }
             _ => false,
         }
     }
@@ -40,6 +51,21 @@ impl Display for Value {
                     vals.push(elem);
                     current = next;
                 }
+                // A list of characters is shown as a string, which its tail may be already
+                let chars = vals
+                    .iter()
+                    .map(|v| match v.as_ref() {
+                        Value::Literal(Literal::Char(c)) => Some(*c),
+                        _ => None,
+                    })
+                    .collect::<Option<String>>();
+                match (chars, current.as_ref()) {
+                    (Some(s), Value::EmptyList) => return write!(f, "{}", Literal::String(s)),
+                    (Some(s), Value::Literal(Literal::String(rest))) => {
+                        return write!(f, "{}", Literal::String(s + rest))
+                    }
+                    _ => {}
+                }
                 if !matches!(current.as_ref(), Value::EmptyList) {
                     vals.push(current);
                 }
@@ -54,6 +80,22 @@ impl Display for Value {
                 )
             }
             Value::EmptyList => write!(f, "[]"),
+            Value::Constructor(name, vs) => {
+                write!(f, "{}", name)?;
+                for v in vs {
+                    match v {
+                        Value::Constructor(_, args) if !args.is_empty() => write!(f, " ({})", v)?,
+                        // Negative numbers are parenthesised like any other compound field
+                        Value::Literal(
+                            Literal::Int(_) | Literal::Integer(_) | Literal::Double(_),
+                        ) if v.to_string().starts_with('-') => write!(f, " ({})", v)?,
+                        v => write!(f, " {}", v)?,
+                    }
+                }
+                Ok(())
+            }
+            Value::Builtin(name, _) => write!(f, "Builtin[{}]", name),
+            Value::Action(name, _) => write!(f, "Action[{}]", name),
             Value::Closure(e, args, _) => write!(f, "Closure[{}]{{ {} }}", args.join(", "), e),
         }
     }
@@ -0,0 +1,11 @@
+assert True = True
+
+-- Annotations pick the type a literal is used at
+big = let (n :: Integer) = 9223372036854775807 in n + 1
+
+half = \(x :: Double) -> x / 2
+
+assert (big > 0)
+assert (half 7 == 3.5)
+assert (fromIntegral (3 :: Int) / 2 == (1.5 :: Double))
+assert (([] :: [Bool]) == [])
@@ -0,0 +1,21 @@
+assert True = True
+
+sign 0 = 0
+sign (-1) = -1
+sign n = 1
+
+assert (-2 ^ 2 == -4)
+assert ((-2) ^ 2 == 4)
+assert (- 3 * 4 + 1 == -11)
+assert (negate 3 == -3)
+assert (7 `div` 2 == 3 && (-7) `div` 2 == -4 && 7 `div` (-2) == -4)
+assert (7 `mod` 2 == 1 && (-7) `mod` 2 == 1 && 7 `mod` (-2) == -1)
+assert (quot (-7) 2 == -3 && rem (-7) 2 == -1)
+assert (div 7 2 == 3 && mod (-7) 2 == 1)
+assert (abs (-4) == 4 && abs 4 == 4)
+assert (signum (-4) == -1 && signum 0 == 0 && signum 4 == 1)
+assert (2 ^ 10 == 1024 && 3 ^ 0 == 1)
+assert (2 ^ 3 ^ 2 == 512)
+assert (10 - 7 `rem` 4 == 7)
+assert ([sign (-1), sign 0, sign 5] == [-1, 0, 1])
+assert ([-3..(-1)] == [-3, -2, -1])
@@ -0,0 +1,38 @@
+assert True = True
+
+data Maybe' a = Just a | Nothing
+
+data Shape = Circle Int | Rect Int Int
+
+describe s = case s of
+  Circle 0 -> "dot"
+  Circle r | r > 10 -> "big circle"
+           | otherwise -> "circle"
+  Rect w h
+    | w == h -> "square"
+  _ -> "shape"
+
+classify n = case n of { 0 -> "zero"; -1 -> "minus one"; _ -> "other" }
+
+firstTwo xs = case xs of
+  (a:b:rest) -> a + b
+  (x:[]) -> x
+  [] -> 0
+
+sumPair p = case p of (a, b) -> a + b where { unused = 1 }
+
+nested m = case m of
+  Just (x, (Just y)) -> case x of
+    0 -> y
+    _ -> x * y
+  _ -> 0
+
+assert (describe (Circle 0) == "dot" && describe (Circle 20) == "big circle")
+assert (describe (Circle 3) == "circle" && describe (Rect 2 2) == "square")
+assert (describe (Rect 2 3) == "shape")
+assert (classify 0 == "zero" && classify (-1) == "minus one" && classify 5 == "other")
+assert (firstTwo [1, 2, 3] == 3 && firstTwo [7] == 7 && firstTwo [] == 0)
+assert (sumPair (3, 4) == 7)
+assert (nested (Just (2, Just 5)) == 10 && nested (Just (0, Just 5)) == 5 && nested Nothing == 0)
+assert (case 3 of 3 -> True; _ -> False)
+assert ((case 1 of { 1 -> 10 }) + 1 == 11)
@@ -0,0 +1,22 @@
+data Color = Red | Green | Blue
+
+class Describe a where { describe :: a -> String ; name :: a -> String ; name x = describe x }
+instance Describe Color where { describe Red = "red" ; describe c = "other" }
+instance Eq Color where { (==) Red Red = True ; (==) Green Green = True ; (==) Blue Blue = True ; (==) x y = False }
+
+assert True = True
+
+elem x [] = False
+elem x (y:ys) = (x == y) || (elem x ys)
+
+maximum :: Ord a => a -> [a] -> a
+maximum m [] = m
+maximum m (x:xs) = if x > m then maximum x xs else maximum m xs
+
+assert (elem Red [Green, Red])
+assert (elem [1] [[2], [1]])
+assert ((maximum 'a' ['z', 'b']) == 'z')
+assert ("abc" < "abd")
+assert ((show 42) == "42")
+assert ((name Blue) == "other")
+assert ((1 + 2 * 3) /= 9)
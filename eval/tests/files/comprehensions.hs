@@ -0,0 +1,18 @@
+data Maybe a = Nothing | Just a
+
+assert True = True
+
+take 0 _ = []
+take n [] = []
+take n (x:xs) = x : take (n - 1) xs
+
+length [] = 0
+length (x:xs) = 1 + length xs
+
+assert ([x * 2 | x <- [1, 2, 3]] == [2, 4, 6])
+assert ([x | x <- [1, 2, 3, 4, 5, 6], x `mod` 2 == 0] == [2, 4, 6])
+assert (length [(x, y) | x <- [1, 2], y <- [True, False]] == 4)
+assert ([y | x <- [1, 2, 3], let y = x * x, y > 3] == [4, 9])
+assert ([x | Just x <- [Just 1, Nothing, Just 3]] == [1, 3])
+assert (take 3 [x * x | x <- [1..], x > 2] == [9, 16, 25])
+assert ([[b | b <- [1..3], a < b] | a <- [1..3]] == [[2, 3], [3], []])
@@ -0,0 +1,11 @@
+data Tree a = Leaf | Node (Tree a) a (Tree a)
+
+insert x Leaf = Node Leaf x Leaf
+insert x (Node l y r) = if x < y then Node (insert x l) y r else Node l y (insert x r)
+
+toList Leaf = []
+toList (Node l x r) = (toList l) ++ (x : (toList r))
+
+toList (insert 2 (insert 3 (insert 1 Leaf)))
+
+insert 2 (insert 1 Leaf)
@@ -0,0 +1,27 @@
+assert True = True
+
+half :: Double -> Double
+half 0 = 0
+half 0.5 = 0.25
+half x = x / 2
+
+mean :: [Int] -> Double
+mean xs = fromIntegral (sum xs) / fromIntegral (count xs) where { sum [] = 0; sum (y:ys) = y + sum ys; count [] = 0; count (y:ys) = 1 + count ys }
+
+nan :: Double
+nan = 0 / 0
+
+assert (7 / 2 == 3.5 && 1.5e3 == 1500 && 2.5e-1 == 0.25)
+assert (half 0 == 0 && half 0.5 == 0.25 && half 3 == 1.5)
+assert (mean [1, 2, 3, 4] == 2.5)
+assert (-1.5 * 2 == -3 && abs (-2.5) == 2.5 && signum (-2.5) == -1)
+assert (truncate (-2.7) == -2 && floor (-2.7) == -3 && ceiling 2.1 == 3)
+assert (round 2.5 == 2 && round 3.5 == 4 && round (-2.5) == -2)
+assert (sqrt 16 == 4 && 2 ** 10 == 1024 && logBase 2 1024 == 10)
+assert (exp 0 == 1 && log 1 == 0 && sin 0 == 0 && cos 0 == 1)
+assert (recip 4 == 0.25 && 0.1 + 0.2 > 0.3)
+assert (show 1.0 == "1.0" && show 0.1 == "0.1" && show 1234567.0 == "1234567.0")
+assert (show 1.0e7 == "1.0e7" && show 0.01 == "1.0e-2" && show (-2.5e-3) == "-2.5e-3")
+assert (show (1 / 0) == "Infinity" && show (0.1 + 0.2) == "0.30000000000000004")
+assert (not (nan < 1 || nan <= 1 || nan > 1 || nan >= 1 || nan == nan))
+assert (not (1 < nan || 1 <= nan || 1 > nan || 1 >= nan) && nan /= nan)
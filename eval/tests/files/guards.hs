@@ -0,0 +1,35 @@
+data Maybe a = Nothing | Just a
+
+assert True = True
+
+sign x
+  | x < 0 = 0 - 1
+  | x == 0 = 0
+  | otherwise = 1
+
+classify n
+  | n < small = "small"
+  | n < big = "medium"
+  where
+    small = 10
+    big = 100
+classify n = "large"
+
+lookup k [] = Nothing
+lookup k ((k2, v):rest)
+  | k == k2 = Just v
+  | otherwise = lookup k rest
+
+find k xs
+  | Just v <- lookup k xs, v > 1 = v
+  | otherwise = 0
+
+assert ((sign 7) == 1)
+assert ((sign 0) == 0)
+assert ((sign (0 - 7)) == (0 - 1))
+assert ((classify 5) == "small")
+assert ((classify 50) == "medium")
+assert ((classify 500) == "large")
+assert ((find 2 [(1, 5), (2, 7)]) == 7)
+assert ((find 1 [(1, 1)]) == 0)
+assert ((find 3 [(1, 5)]) == 0)
@@ -6,3 +6,4 @@ nat = [1, 2..]
 even = map \x -> 2 * x nat
 
 odd = map (\x -> 2 * x - 1) nat
+tens = map (* 10) nat
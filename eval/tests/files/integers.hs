@@ -0,0 +1,16 @@
+assert True = True
+
+big :: Integer
+big = 9223372036854775807 + 1
+
+small :: Int
+small = 9223372036854775807
+
+assert (big * big == 85070591730234615865843651857942052864)
+assert (big == 9223372036854775808 && big > toInteger small)
+assert (toInteger small + 1 == big)
+assert (fromIntegral small - big == -1)
+assert (big `div` (-3) == -3074457345618258603 && big `mod` (-3) == -1)
+assert (2 ^ 64 == 18446744073709551616)
+assert ([big..big + 2] == [9223372036854775808, 9223372036854775809, 9223372036854775810])
+assert (show big == "9223372036854775808")
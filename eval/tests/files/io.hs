@@ -0,0 +1,15 @@
+echo = getLine >>= putStrLn
+
+main = do
+  putStrLn "What is your name?"
+  name <- getLine
+  let greeting = "Hello, "
+  putStr greeting
+  putStrLn name
+  echo
+  (n, b) <- return (6 * 7, True)
+  if b then print n else return ()
+  print (n > 40)
+  print 1.5
+  rest <- getContents
+  putStr rest
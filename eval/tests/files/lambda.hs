@@ -0,0 +1,14 @@
+data Maybe a = Nothing | Just a
+
+assert True = True
+
+add = \x y -> x + y
+first = \(a, b) -> a
+heads = \(x:xs) (y:ys) -> x + y
+fromJust = \_ (Just v) -> v
+
+assert ((add 1 2) == 3)
+assert ((first (1, True)) == 1)
+assert ((heads [1, 2] [3]) == 4)
+assert ((fromJust 0 (Just 5)) == 5)
+assert (((\x -> \(a, b) -> x + a) 1 (2, 3)) == 3)
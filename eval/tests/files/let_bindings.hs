@@ -0,0 +1,30 @@
+assert True = True
+
+fact n = let
+           go 0 acc = acc
+           go k acc = go (k - 1) (acc * k)
+         in go n 1
+
+parity n = let
+             isEven 0 = True
+             isEven m = isOdd (m - 1)
+             isOdd 0 = False
+             isOdd m = isEven (m - 1)
+           in isEven n
+
+twice = let
+          same :: Eq a => a -> a -> Bool
+          same u v = u == v
+        in (same 1 1) && (not (same 'a' 'b'))
+
+prefix = let
+           ones = 1 : ones
+           take 0 ys = []
+           take k (y:ys) = y : (take (k - 1) ys)
+         in take 3 ones
+
+assert ((let { x = 1 ; y = x + 1 } in x + y) == 3)
+assert ((fact 5) == 120)
+assert ((parity 8) && (not (parity 7)))
+assert twice
+assert (prefix == [1, 1, 1])
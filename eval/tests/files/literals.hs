@@ -0,0 +1,10 @@
+assert True = True
+
+next' c' = if c' == '\DEL' then '\NUL' else c'
+
+assert ('\x41' == 'A' && '\o101' == 'A' && '\65' == 'A' && '\^A' == '\SOH')
+assert ("tab\there" == "tab\9here" && "\SO\&H" /= "\SOH")
+assert (show "say \"hi\"\n" == "\"say \\\"hi\\\"\\n\"")
+assert (show 'λ' == "'\\955'" && show "λ1" == "\"\\955\\&1\"")
+assert (next' '\DEL' == '\0' && next' 'x' == 'x')
+assert (0xFF == 255 && 0o17 == 15 && 0b101 == 5 && 0x10000000000000000 == 18446744073709551616)
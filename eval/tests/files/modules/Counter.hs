@@ -0,0 +1,9 @@
+module Counter (next, count) where
+
+step = 1
+
+next :: Int -> Int
+next n = n + step
+
+count :: Int -> [Int]
+count n = [next 0 .. n]
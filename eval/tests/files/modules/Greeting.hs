@@ -0,0 +1,6 @@
+module Greeting where
+
+step = "Hello, "
+
+greet :: String -> IO ()
+greet name = putStr step >> putStrLn name
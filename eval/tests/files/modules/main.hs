@@ -0,0 +1,10 @@
+import Counter
+import qualified Greeting as G
+
+step = 10
+
+main = do
+  G.greet "modules"
+  print (next step)
+  print (count 3 == [1, 2, 3])
+  print (Counter.next 0)
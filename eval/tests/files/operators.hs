@@ -0,0 +1,38 @@
+data Maybe a = Nothing | Just a
+
+assert True = True
+
+infixr 9 .
+infixr 0 $
+infixl 9 !!
+infixl 3 <|>
+infixl 6 <->
+
+(.) f g x = f (g x)
+f $ x = f x
+
+(x:xs) !! 0 = x
+(x:xs) !! n = xs !! (n - 1)
+
+Nothing <|> y = y
+Just x <|> y = Just x
+
+a <-> b = a - b
+
+fromMaybe d Nothing = d
+fromMaybe d (Just x) = x
+
+map f [] = []
+map f (x:xs) = (f x) : (map f xs)
+
+inc x = x + 1
+double x = 2 * x
+
+assert (((double . inc) 3) == 8)
+assert ((double $ inc $ 3) == 8)
+assert (([1, 2, 3] !! 2) == 3)
+assert (((fromMaybe 0) $ Nothing <|> (Just 1) <|> (Just 2)) == 1)
+assert ((10 <-> 2 <-> 3) == 5)
+assert ((10 - 2 - 3) == 5)
+assert (((<->) 5 2) == 3)
+assert ((map (<-> 1) [2, 3]) == [1, 2])
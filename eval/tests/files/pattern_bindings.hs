@@ -0,0 +1,23 @@
+assert True = True
+
+data Maybe a = Nothing | Just a
+
+(a, b) = (1, [2, 3])
+x:xs = "hello"
+
+digits n = let (q, r) = (div n 10, mod n 10) in [q, r]
+
+total = c + d
+  where
+    (c, d) = (a, 2)
+
+-- The pattern is only matched once one of its variables is demanded
+unused = let [y] = [1, 2] in 5
+unmatched = let Just z = Nothing in z
+
+assert (a == 1)
+assert (b == [2, 3])
+assert (x == 'h' && xs == "ello")
+assert (digits 42 == [4, 2])
+assert (total == 3)
+assert (unused == 5)
@@ -0,0 +1,27 @@
+assert True = True
+
+data Maybe a = Nothing | Just a
+
+pairSums ((a, b):rest) = a + b : pairSums rest
+pairSums [] = []
+
+firstTwo [x, y] = [y, x]
+firstTwo _ = []
+
+dup xs@(x:_) = x : xs
+dup [] = []
+
+pairDiff (Just (x, y):rest) = x - y
+pairDiff _ = 0
+
+lastOf l = case l of
+  [x] -> x
+  _:xs@(_:_) -> lastOf xs
+
+assert (pairSums [(1, 2), (3, 4)] == [3, 7])
+assert (firstTwo [1, 2] == [2, 1])
+assert (firstTwo [1, 2, 3] == [])
+assert (dup [1, 2] == [1, 1, 2])
+assert (pairDiff [Just (5, 2)] == 3)
+assert (pairDiff [Nothing] == 0)
+assert (lastOf "abc" == 'c')
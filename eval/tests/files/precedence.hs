@@ -0,0 +1,15 @@
+assert True = True
+
+map f [] = []
+map f (x:xs) = f x : map f xs
+
+inc x = x + 1
+
+assert (10 - 2 - 3 == 5)
+assert (1 + 2 * 3 == 7)
+assert (inc 2 * inc 3 == 12)
+assert (map inc [1, 2] ++ [5] == [2, 3, 5])
+assert (2 : 3 : [] ++ [4] == [2, 3, 4])
+assert (True || False && False)
+assert (10 - 2 * 3 `mod` 4 == 8)
+assert (not (1 < 2 && 3 < 2))
@@ -0,0 +1,24 @@
+assert True = True
+
+take 0 _ = []
+take n [] = []
+take n (x:xs) = x : take (n - 1) xs
+
+upTo n = [1..n]
+
+assert (upTo 5 == [1, 2, 3, 4, 5])
+assert (upTo 0 == [])
+assert ([10, 8..0] == [10, 8, 6, 4, 2, 0])
+assert ([10, 8..1] == [10, 8, 6, 4, 2])
+assert (take 3 [5, 3..] == [5, 3, 1])
+assert (['a'..'e'] == ['a', 'b', 'c', 'd', 'e'])
+assert (['a', 'c'..'i'] == ['a', 'c', 'e', 'g', 'i'])
+assert (take 3 [1, 1..5] == [1, 1, 1])
+assert ([1, 1..0] == [])
+assert ([x | x <- [1..10], x > 7] == [8, 9, 10])
+-- A range without an end stops at the last value of its type
+assert (([9223372036854775806 ..] :: [Int]) == [9223372036854775806, 9223372036854775807])
+assert (([9223372036854775803, 9223372036854775805 ..] :: [Int]) == [9223372036854775803, 9223372036854775805, 9223372036854775807])
+assert (['\1114109' ..] == ['\1114109', '\1114110', '\1114111'])
+assert (['\1114109' .. '\1114111'] == ['\1114109', '\1114110', '\1114111'])
+assert (['\1114108', '\1114110' ..] == ['\1114108', '\1114110'])
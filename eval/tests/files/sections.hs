@@ -0,0 +1,22 @@
+data Pair a b = Pair a b
+
+assert True = True
+
+map f [] = []
+map f (x:xs) = (f x) : (map f xs)
+
+foldr f z [] = z
+foldr f z (x:xs) = f x (foldr f z xs)
+
+max x y = if x < y then y else x
+
+assert ((map (* 2) [1, 2, 3]) == [2, 4, 6])
+assert ((map (10 -) [1, 2, 3]) == [9, 8, 7])
+assert ((map (`max` 2) [1, 2, 3]) == [2, 2, 3])
+assert ((foldr (+) 0 [1, 2, 3]) == 6)
+assert ((foldr (++) [] [[1], [2, 3]]) == [1, 2, 3])
+assert ((foldr (:) [] [1, 2]) == [1, 2])
+assert ((map (`mod` 3) [4, 5]) == [1, 2])
+assert ((3 `max` 4) == 4)
+assert (((5 `mod`) 3) == 2)
+assert (((&&) True False) == False)
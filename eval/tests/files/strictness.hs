@@ -0,0 +1,61 @@
+assert True = True
+
+data Maybe a = Nothing | Just a
+
+failing = let Just z = Nothing in z
+
+sumTo !acc 0 = acc
+sumTo !acc n = sumTo (acc + n) (n - 1)
+
+-- Forcing the accumulator on every call keeps loops like these from building up a chain of
+-- additions as deep as the loop, which would overflow the stack once it is forced
+sumStrict acc 0 = acc
+sumStrict acc n = (sumStrict $! acc + n) (n - 1)
+
+-- The same holds for loops over a list, whose cells are let go of as the loop goes on
+sumBang :: Int -> [Int] -> Int
+sumBang !acc [] = acc
+sumBang !acc (x:xs) = sumBang (acc + x) xs
+
+sumSeq :: Int -> [Int] -> Int
+sumSeq acc [] = acc
+sumSeq acc (x:xs) = let acc' = acc + x in acc' `seq` sumSeq acc' xs
+
+sumApply :: Int -> [Int] -> Int
+sumApply acc [] = acc
+sumApply acc (x:xs) = (sumApply $! acc + x) xs
+
+-- A lazy pattern is never matched if none of its variables is used
+lazyFst ~(a, b) = 1
+irrefutable = let f ~(Just x) = 0 in f Nothing
+pairSum = let ~(a, b) = (1, 2) in a + b
+
+strictLet = let !x = 1 + 2 in x * 2
+forced = (\x -> x + 1) $! 5
+ignored = const 1 failing
+  where
+    const a b = a
+strictWhere = y * 2
+  where
+    !y = 1 + 2
+-- The bindings are forced before the guards, which still fall through when they fail
+pick x | x > 0 = y
+  where
+    !y = x * 2
+pick x = 0
+
+assert (sumTo 0 5 == 15)
+assert (sumTo 0 3000 == 4501500)
+assert (sumStrict 0 3000 == 4501500)
+assert (sumBang 0 [1..100000] == 5000050000)
+assert (sumSeq 0 [1..100000] == 5000050000)
+assert (sumApply 0 [1..100000] == 5000050000)
+assert (lazyFst failing == 1)
+assert (irrefutable == 0)
+assert (pairSum == 3)
+assert (strictLet == 6)
+assert (forced == 6)
+assert (ignored == 1)
+assert (strictWhere == 6)
+assert (pick 3 == 6 && pick 0 == 0)
+assert ([failing] `seq` True)
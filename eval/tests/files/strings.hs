@@ -0,0 +1,17 @@
+shout :: String -> String
+shout [] = "!"
+shout (c:cs) = c : shout cs
+
+greet "World" = "Hi, World"
+greet name = "Hello, " ++ name
+
+main = do
+  putStrLn (shout "hey")
+  putStrLn (greet ('W' : "orld"))
+  putStrLn (greet "you")
+  print "quoted"
+  print [1, 2]
+  print (['a'..'c'] == "abc")
+  print ("abc" < "abd" && "ab" < ['a', 'b', 'c'])
+  name <- getLine
+  putStrLn (name ++ ['.'])
@@ -0,0 +1,8 @@
+assert True = True
+
+assert ((1, 2) == (1, 2))
+assert ((1, 'a') /= (1, 'b'))
+assert ((True, [1], ()) == (True, [1], ()))
+assert (() == ())
+assert (show (1, True) == "(1,True)")
+assert (show ((), (1, [2, 3]), "s") == "((),(1,[2,3]),\"s\")")
@@ -0,0 +1,28 @@
+assert True = True
+
+fact n = go n 1
+  where
+    go 0 acc = acc
+    go k acc = go (k - 1) (acc * k)
+
+evens = take 4 xs
+  where
+    xs = 0 : (map (\x -> x + 2) xs)
+    map f [] = []
+    map f (y:ys) = (f y) : (map f ys)
+    take 0 ys = []
+    take k (y:ys) = y : (take (k - 1) ys)
+
+parity n = isEven n
+  where
+    isEven 0 = True
+    isEven m = isOdd (m - 1)
+    isOdd 0 = False
+    isOdd m = isEven (m - 1)
+
+lazy x = x where loop = loop
+
+assert ((fact 5) == 120)
+assert (evens == [0, 2, 4, 6])
+assert (parity 10)
+assert ((lazy 3) == 3)
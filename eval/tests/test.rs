@@ -1,5 +1,7 @@
-use eval::eval;
-use parser::parse;
+use eval::{eval, eval_with, run_main, Overflow};
+use parser::{load, parse};
+use std::path::Path;
+use typechecker::{typecheck, typecheck_modules};
 
 #[test]
 fn fib() {
@@ -24,3 +26,322 @@ fn infinite_ranges() {
     let ast = parse(src).unwrap();
     eval(ast).unwrap()
 }
+
+#[test]
+fn data_constructors() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/data.hs");
+    let ast = parse(src).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn type_classes() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/classes.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn where_bindings() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/where.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn guards() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/guards.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn let_bindings() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/let_bindings.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn lambdas() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/lambda.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn lambda_pattern_failure() {
+    let _ = env_logger::try_init();
+    let src = "heads = \\(x:xs) (y:ys) -> x + y\nheads [] [1]\n";
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    let err = eval(ast).unwrap_err();
+    assert_eq!(err.to_string(), "Found non Exhaustive pattern in function");
+}
+
+#[test]
+fn sections() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/sections.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn user_operators() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/operators.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn precedence() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/precedence.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn list_comprehensions() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/comprehensions.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn ranges() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/ranges.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn arithmetic() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/arithmetic.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn division_by_zero() {
+    let _ = env_logger::try_init();
+    for src in ["1 `div` 0\n", "mod 1 0\n", "quot 1 0\n", "rem 1 0\n"] {
+        let ast = parse(src).unwrap();
+        let ast = typecheck(&ast).unwrap();
+        let err = eval(ast).unwrap_err();
+        assert_eq!(err.to_string(), "Division by zero");
+    }
+}
+
+#[test]
+fn integers() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/integers.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn int_overflow() {
+    let _ = env_logger::try_init();
+    let src = "inc :: Int -> Int\ninc x = x + 1\nassert True = True\nassert (inc 9223372036854775807 < 0)\n";
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    let err = eval(ast.clone()).unwrap_err();
+    assert_eq!(err.to_string(), "Arithmetic overflow on Int");
+    eval_with(ast, Overflow::Wrapping).unwrap()
+}
+
+#[test]
+fn doubles() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/doubles.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn annotations() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/annotations.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn patterns() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/patterns.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn pattern_bindings() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/pattern_bindings.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap();
+    let src = format!("{}unmatched\n", src);
+    let ast = parse(&src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    let err = eval(ast).unwrap_err();
+    assert_eq!(err.to_string(), "Found non Exhaustive pattern in function");
+}
+
+#[test]
+fn strictness() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/strictness.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap();
+    // Forcing a value which does not fit its pattern binding fails even if it is not used
+    let failing = "data Maybe a = Nothing | Just a\nfailing = let Just z = Nothing in z\n";
+    for forcing in [
+        "failing `seq` 1",
+        "(\\x -> 1) $! failing",
+        "let !y = failing in 1",
+        "forcedWhere = 1 where { !y = failing }\nforcedWhere",
+    ] {
+        let src = format!("{}{}\n", failing, forcing);
+        let ast = parse(&src).unwrap();
+        let ast = typecheck(&ast).unwrap();
+        let err = eval(ast).unwrap_err();
+        assert_eq!(err.to_string(), "Found non Exhaustive pattern in function");
+    }
+}
+
+#[test]
+fn long_lists() {
+    let _ = env_logger::try_init();
+    let ast = typecheck(&parse("main = print [1..1000]\n").unwrap()).unwrap();
+    let mut output = vec![];
+    run_main(ast, &mut "".as_bytes(), &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("[1,2,3,") && output.ends_with(",999,1000]\n"));
+}
+
+#[test]
+fn tuple_instances() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/tuple_instances.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn untyped_annotations() {
+    let _ = env_logger::try_init();
+    let src = "assert True = True\ndec = \\(x :: Int) -> x - 1\nassert ((1 :: Int) + dec 3 == 3)\n";
+    let ast = parse(src).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn non_finite_conversion() {
+    let _ = env_logger::try_init();
+    for src in ["truncate (1 / 0)\n", "round (0 / 0)\n"] {
+        let ast = parse(src).unwrap();
+        let ast = typecheck(&ast).unwrap();
+        let err = eval(ast).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot convert NaN or Infinity to an integer"
+        );
+    }
+}
+
+#[test]
+fn literals() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/literals.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap()
+}
+
+#[test]
+fn case_of() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/case.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    eval(ast).unwrap();
+    let ast = parse("case 2 of { 1 -> True }\n").unwrap();
+    let ast = typecheck(&ast).unwrap();
+    let err = eval(ast).unwrap_err();
+    assert_eq!(err.to_string(), "Found non Exhaustive pattern in function");
+}
+
+#[test]
+fn io() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/io.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    let mut output = vec![];
+    run_main(
+        ast,
+        &mut "World\nagain\nrest\nof it\n".as_bytes(),
+        &mut output,
+    )
+    .unwrap();
+    let expected = "What is your name?\nHello, World\nagain\n42\nTrue\n1.5\nrest\nof it\n";
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+    let ast = typecheck(&parse("main = getLine >>= putStrLn\n").unwrap()).unwrap();
+    let err = run_main(ast, &mut "".as_bytes(), &mut vec![]).unwrap_err();
+    assert_eq!(err.to_string(), "Reached the end of the input");
+}
+
+#[test]
+fn strings() {
+    let _ = env_logger::try_init();
+    let src = include_str!("./files/strings.hs");
+    let ast = parse(src).unwrap();
+    let ast = typecheck(&ast).unwrap();
+    let mut output = vec![];
+    run_main(ast, &mut "Bob\n".as_bytes(), &mut output).unwrap();
+    let expected = "hey!\nHi, World\nHello, you\n\"quoted\"\n[1,2]\nTrue\nTrue\nBob.\n";
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+}
+
+#[test]
+fn modules() {
+    let _ = env_logger::try_init();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/files/modules");
+    let modules = load(&dir.join("main.hs"), &[]).unwrap();
+    let ast = typecheck_modules(&modules).unwrap();
+    let mut output = vec![];
+    run_main(ast, &mut "".as_bytes(), &mut output).unwrap();
+    let expected = "Hello, modules\n11\nTrue\n1\n";
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+}
@@ -14,4 +14,28 @@ pub enum ParsingError {
     GrammarError,
     #[error("Equations for ‘{0}’ have different numbers of arguments")]
     VaryingArity(String),
+    #[error("Layout error at line {0}, column {1}: {2}")]
+    LayoutError(usize, usize, String),
+    #[error("Cannot mix '{0}' and '{1}' in the same infix expression")]
+    FixityConflict(String, String),
+    #[error("Invalid literal {0}")]
+    InvalidLiteral(String),
+    #[error("The last statement of a do block must be an expression")]
+    InvalidDoBlock,
+    #[error("Could not read {0}: {1}")]
+    ReadError(String, String),
+    #[error("Could not find module {0}")]
+    ModuleNotFound(String),
+    #[error("{0} holds module {1} rather than {2}")]
+    WrongModuleName(String, String, String),
+    #[error("Modules import each other in a cycle: {0}")]
+    ImportCycle(String),
+    #[error("Module {0} does not export {1}")]
+    NotExported(String, String),
+    #[error("Module {0} exports {1}, which is not in scope")]
+    UnknownExport(String, String),
+    #[error("Ambiguous name {0}, which could refer to {1} or {2}")]
+    AmbiguousName(String, String, String),
+    #[error("Strict binding at line {0}, column {1} is not allowed at the top level")]
+    StrictTopLevel(usize, usize),
 }
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+use ast::ast::{Decl, Expr, Guard, InfixOp, List, Literal, Op, Program, Rhs};
+
+use crate::error::ParsingError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+    None,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Fixity {
+    pub associativity: Associativity,
+    pub precedence: u8,
+}
+
+/// The fixities declared in a program, by operator name
+pub type Fixities = HashMap<String, Fixity>;
+
+/// The fixity of operators without a fixity declaration
+const DEFAULT_FIXITY: Fixity = Fixity {
+    associativity: Associativity::Left,
+    precedence: 9,
+};
+
+/// The fixity of prefix negation, which is that of binary `-`
+const NEGATION: Fixity = Fixity {
+    associativity: Associativity::Left,
+    precedence: 6,
+};
+
+/// The name a fixity declaration refers to an operator by
+pub fn key(op: &InfixOp) -> String {
+    match op {
+        InfixOp::Builtin(Op::Mod) => "mod".to_string(),
+        InfixOp::Builtin(op) => op.to_string(),
+        InfixOp::Named(name) => name.clone(),
+    }
+}
+
+fn fixity(op: &InfixOp, fixities: &Fixities) -> Fixity {
+    if let Some(fixity) = fixities.get(&key(op)) {
+        return *fixity;
+    }
+    let (associativity, precedence) = match op {
+        InfixOp::Named(name) if matches!(name.as_str(), "^" | "**") => (Associativity::Right, 8),
+        InfixOp::Builtin(Op::Mul | Op::Div | Op::Mod) => (Associativity::Left, 7),
+        InfixOp::Named(name) if matches!(name.as_str(), "div" | "quot" | "rem") => {
+            (Associativity::Left, 7)
+        }
+        InfixOp::Builtin(Op::Add | Op::Sub) => (Associativity::Left, 6),
+        InfixOp::Builtin(Op::Cons | Op::Append) => (Associativity::Right, 5),
+        InfixOp::Builtin(Op::Eq | Op::Neq | Op::Lt | Op::Gt | Op::Le | Op::Ge) => {
+            (Associativity::None, 4)
+        }
+        InfixOp::Builtin(Op::And) => (Associativity::Right, 3),
+        InfixOp::Builtin(Op::Or) => (Associativity::Right, 2),
+        InfixOp::Named(name) if matches!(name.as_str(), ">>" | ">>=") => (Associativity::Left, 1),
+        InfixOp::Named(name) if matches!(name.as_str(), "$!" | "seq") => (Associativity::Right, 0),
+        InfixOp::Named(_) => return DEFAULT_FIXITY,
+    };
+    Fixity {
+        associativity,
+        precedence,
+    }
+}
+
+/// The function an operator stands for
+pub fn operator_expr(op: InfixOp) -> Expr {
+    match op {
+        InfixOp::Builtin(op) => Expr::Operator(op),
+        InfixOp::Named(name) if name.starts_with(char::is_uppercase) => Expr::Constructor(name),
+        InfixOp::Named(name) => Expr::Var(name),
+    }
+}
+
+/// The negation of an expression, which is folded into numeric literals
+pub fn negate(e: Expr) -> Expr {
+    match e {
+        Expr::Literal(Literal::Int(n)) => Expr::Literal(Literal::Int(-n)),
+        Expr::Literal(Literal::Integer(n)) => Expr::Literal(Literal::Integer(-n)),
+        Expr::Literal(Literal::Double(x)) => Expr::Literal(Literal::Double(-x)),
+        e => Expr::Application(Box::new(Expr::Var("negate".to_string())), Box::new(e)),
+    }
+}
+
+pub fn apply(op: InfixOp, left: Expr, right: Expr) -> Expr {
+    match op {
+        InfixOp::Builtin(op) => Expr::BinOp(Box::new(left), op, Box::new(right)),
+        op => {
+            let partial = Expr::Application(Box::new(operator_expr(op)), Box::new(left));
+            Expr::Application(Box::new(partial), Box::new(right))
+        }
+    }
+}
+
+/// Groups the operands of all infix expressions in a program according to the fixities of their
+/// operators
+pub fn resolve_program(program: Program, fixities: &Fixities) -> Result<Program, ParsingError> {
+    program
+        .into_iter()
+        .map(|decl| resolve_decl(decl, fixities))
+        .collect()
+}
+
+fn resolve_decl(decl: Decl, fixities: &Fixities) -> Result<Decl, ParsingError> {
+    Ok(match decl {
+        Decl::FunDecl(name, args, e) => Decl::FunDecl(name, args, resolve_expr(e, fixities)?),
+        Decl::ClassDecl(context, name, var, decls) => {
+            Decl::ClassDecl(context, name, var, resolve_program(decls, fixities)?)
+        }
+        Decl::InstanceDecl(context, name, t, decls) => {
+            Decl::InstanceDecl(context, name, t, resolve_program(decls, fixities)?)
+        }
+        Decl::SExpr(e) => Decl::SExpr(resolve_expr(e, fixities)?),
+        decl => decl,
+    })
+}
+
+fn resolve_expr(expr: Expr, fixities: &Fixities) -> Result<Expr, ParsingError> {
+    let resolve = |e: Box<Expr>| resolve_expr(*e, fixities).map(Box::new);
+    Ok(match expr {
+        Expr::Infix(first, rest) => {
+            let first = resolve_expr(*first, fixities)?;
+            let rest = rest
+                .into_iter()
+                .map(|(op, e)| Ok((op, resolve_expr(e, fixities)?)))
+                .collect::<Result<Vec<_>, ParsingError>>()?;
+            let mut rest = rest.into_iter().peekable();
+            let first = operand(first, &mut rest, fixities)?;
+            climb(first, &mut rest, 0, fixities)?
+        }
+        Expr::Negate(e) => Expr::Negate(resolve(e)?),
+        Expr::Annotated(e, t) => Expr::Annotated(resolve(e)?, t),
+        Expr::Application(f, e) => Expr::Application(resolve(f)?, resolve(e)?),
+        Expr::If(a, b, c) => Expr::If(resolve(a)?, resolve(b)?, resolve(c)?),
+        Expr::Let(decls, e) => Expr::Let(resolve_program(decls, fixities)?, resolve(e)?),
+        Expr::Lambda(arg, e) => Expr::Lambda(arg, resolve(e)?),
+        Expr::Case(e, cases) => Expr::Case(
+            resolve(e)?,
+            cases
+                .into_iter()
+                .map(|(p, rhs)| Ok((p, resolve_rhs(rhs, fixities)?)))
+                .collect::<Result<_, ParsingError>>()?,
+        ),
+        Expr::BinOp(l, op, r) => Expr::BinOp(resolve(l)?, op, resolve(r)?),
+        Expr::Tuple(es) => Expr::Tuple(
+            es.into_iter()
+                .map(|e| resolve_expr(e, fixities))
+                .collect::<Result<_, _>>()?,
+        ),
+        Expr::List(ls) => Expr::List(resolve_list(ls, fixities)?),
+        Expr::Range(from, then, to) => Expr::Range(
+            resolve(from)?,
+            then.map(resolve).transpose()?,
+            to.map(resolve).transpose()?,
+        ),
+        e @ (Expr::Var(_) | Expr::Constructor(_) | Expr::Literal(_) | Expr::Operator(_)) => e,
+    })
+}
+
+fn resolve_rhs(rhs: Rhs, fixities: &Fixities) -> Result<Rhs, ParsingError> {
+    Ok(match rhs {
+        Rhs::Expr(e) => Rhs::Expr(resolve_expr(e, fixities)?),
+        Rhs::Guarded(alternatives) => Rhs::Guarded(
+            alternatives
+                .into_iter()
+                .map(|(guards, e)| {
+                    let guards = guards
+                        .into_iter()
+                        .map(|guard| match guard {
+                            Guard::Bool(cond) => Ok(Guard::Bool(resolve_expr(cond, fixities)?)),
+                            Guard::Pattern(p, e) => {
+                                Ok(Guard::Pattern(p, resolve_expr(e, fixities)?))
+                            }
+                        })
+                        .collect::<Result<_, ParsingError>>()?;
+                    Ok((guards, resolve_expr(e, fixities)?))
+                })
+                .collect::<Result<_, ParsingError>>()?,
+        ),
+        Rhs::Where(decls, rhs) => Rhs::Where(
+            resolve_program(decls, fixities)?,
+            Box::new(resolve_rhs(*rhs, fixities)?),
+        ),
+    })
+}
+
+fn resolve_list(ls: List<Expr>, fixities: &Fixities) -> Result<List<Expr>, ParsingError> {
+    Ok(match ls {
+        List::Some(e, es) => List::Some(
+            Box::new(resolve_expr(*e, fixities)?),
+            Box::new(resolve_list(*es, fixities)?),
+        ),
+        List::Empty => List::Empty,
+    })
+}
+
+/// Precedence climbing: applies the operators binding at least as tightly as `min_precedence`,
+/// starting with `left` as the leftmost operand
+fn climb(
+    mut left: Expr,
+    rest: &mut Peekable<IntoIter<(InfixOp, Expr)>>,
+    min_precedence: u8,
+    fixities: &Fixities,
+) -> Result<Expr, ParsingError> {
+    while let Some((op, _)) = rest.peek() {
+        let current = fixity(op, fixities);
+        if current.precedence < min_precedence {
+            break;
+        }
+        let Some((op, mut right)) = rest.next() else {
+            break;
+        };
+        if let Expr::Negate(_) = right {
+            // As in Haskell, `a * -b` needs parentheses but `a == -b` does not
+            if current.precedence >= NEGATION.precedence {
+                return Err(ParsingError::FixityConflict(key(&op), "-".to_string()));
+            }
+            right = operand(right, rest, fixities)?;
+        }
+        while let Some((next_op, _)) = rest.peek() {
+            let next = fixity(next_op, fixities);
+            if next.precedence > current.precedence {
+                right = climb(right, rest, current.precedence + 1, fixities)?;
+            } else if next.precedence < current.precedence {
+                break;
+            } else {
+                match (current.associativity, next.associativity) {
+                    (Associativity::Right, Associativity::Right) => {
+                        right = climb(right, rest, current.precedence, fixities)?
+                    }
+                    (Associativity::Left, Associativity::Left) => break,
+                    _ => return Err(ParsingError::FixityConflict(key(&op), key(next_op))),
+                }
+            }
+        }
+        left = apply(op, left, right);
+    }
+    Ok(left)
+}
+
+/// Applies a negated operand to the operators following it that bind more tightly than negation
+fn operand(
+    e: Expr,
+    rest: &mut Peekable<IntoIter<(InfixOp, Expr)>>,
+    fixities: &Fixities,
+) -> Result<Expr, ParsingError> {
+    match e {
+        Expr::Negate(e) => Ok(negate(climb(*e, rest, NEGATION.precedence + 1, fixities)?)),
+        e => Ok(e),
+    }
+}
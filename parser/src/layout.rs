@@ -0,0 +1,390 @@
+use pest::error::{Error, InputLocation};
+use pest::Position;
+
+use crate::error::ParsingError;
+
+/// Keywords whose following block is subject to the layout rule
+const BLOCK_KEYWORDS: [&str; 4] = ["let", "where", "of", "do"];
+
+/// Tokens that may start a line continuing an expression after the blocks inside it were closed
+const CONTINUATION_TOKENS: [&str; 9] = ["in", "then", "else", "of", "where", ")", "]", ",", "}"];
+
+#[derive(Debug)]
+struct Token<'a> {
+    text: &'a str,
+    start: usize,
+    end: usize,
+    line: usize,
+    column: usize,
+    first_on_line: bool,
+}
+
+/// A source file after the layout rule, which keeps track of where its text comes from
+pub struct Layout {
+    pub text: String,
+    /// The pieces of the text, each with the offset it starts at in the text and the offset and
+    /// length of what it comes from in the source. Braces and semicolons inserted by the layout
+    /// rule come from nothing.
+    pieces: Vec<(usize, usize, usize)>,
+}
+
+impl Layout {
+    /// The offset in the source of an offset in the text
+    pub fn source_offset(&self, offset: usize) -> usize {
+        let i = self
+            .pieces
+            .partition_point(|(start, _, _)| *start <= offset);
+        match i.checked_sub(1).map(|i| self.pieces[i]) {
+            Some((start, source, len)) => source + (offset - start).min(len),
+            None => 0,
+        }
+    }
+
+    /// The offset in the text of a line and column in it
+    pub fn offset(&self, line: usize, column: usize) -> usize {
+        let start = match line {
+            1 => 0,
+            line => self
+                .text
+                .match_indices('\n')
+                .nth(line - 2)
+                .map_or(self.text.len(), |(i, _)| i + 1),
+        };
+        self.text[start..]
+            .char_indices()
+            .nth(column - 1)
+            .map_or(self.text.len(), |(i, _)| start + i)
+    }
+
+    /// Points an error found in the text at the place in the source it comes from, whose line and
+    /// column it reports and whose line it shows
+    pub fn locate(&self, err: ParsingError, source: &str) -> ParsingError {
+        let position = |offset: usize| {
+            let mut offset = self.source_offset(offset);
+            while !source.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            Position::new(source, offset).expect("Offsets are at character boundaries")
+        };
+        match err {
+            ParsingError::PestError(err) => ParsingError::PestError(match err.location {
+                InputLocation::Pos(pos) => Error::new_from_pos(err.variant, position(pos)),
+                InputLocation::Span((start, end)) => {
+                    Error::new_from_span(err.variant, position(start).span(&position(end)))
+                }
+            }),
+            ParsingError::StrictTopLevel(line, column) => {
+                let (line, column) = position(self.offset(line, column)).line_col();
+                ParsingError::StrictTopLevel(line, column)
+            }
+            err => err,
+        }
+    }
+
+    fn push(&mut self, text: &str, source: usize, len: usize) {
+        self.pieces.push((self.text.len(), source, len));
+        self.text.push_str(text);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Context<'a> {
+    /// A block laid out by indentation, opened by the keyword at the given column
+    Implicit(usize, &'a str),
+    /// A block delimited by explicit braces
+    Explicit,
+}
+
+/// Applies the layout rule to a source file: blocks after `let`, `where`, `of` and `do` are delimited
+/// with explicit braces and semicolons, and lines continuing a top-level declaration are joined
+/// onto it, so that the grammar sees exactly one top-level declaration per line.
+pub fn resolve_layout(source: &str) -> Result<Layout, ParsingError> {
+    let tokens = tokenize(source);
+    let top_column = tokens.first().map(|t| t.column).unwrap_or(1);
+    let mut layout: Vec<Context> = vec![];
+    // Open brackets and `if`s, with the number of layout contexts open at that point
+    let mut brackets: Vec<(&str, usize)> = vec![];
+    let mut block_keyword: Option<&str> = None;
+    // The `where` of a module header is followed by the top-level declarations, not a block
+    let mut in_header = tokens.first().is_some_and(|t| t.text == "module");
+    let mut out = Layout {
+        text: String::with_capacity(source.len()),
+        pieces: vec![],
+    };
+    let mut last_end = 0;
+
+    for token in &tokens {
+        let gap = &source[last_end..token.start];
+        let mut prefix = String::new();
+        let mut new_declaration = false;
+        let mut opened_block = false;
+
+        if let Some(keyword) = block_keyword.take() {
+            if token.text != "{" {
+                let enclosing = match layout.last() {
+                    Some(Context::Implicit(column, _)) => *column,
+                    Some(Context::Explicit) => 0,
+                    None => top_column,
+                };
+                if token.column > enclosing {
+                    layout.push(Context::Implicit(token.column, keyword));
+                    prefix.push_str(" { ");
+                    opened_block = true;
+                } else {
+                    prefix.push_str(" {} ");
+                }
+            }
+        }
+        if token.first_on_line && !opened_block {
+            let mut closed = false;
+            while let Some(Context::Implicit(column, _)) = layout.last() {
+                if token.column >= *column {
+                    break;
+                }
+                layout.pop();
+                prefix.push_str(" } ");
+                closed = true;
+            }
+            let aligned = match layout.last() {
+                Some(Context::Implicit(column, _)) => token.column == *column,
+                Some(Context::Explicit) => false,
+                None if brackets.is_empty() => {
+                    if token.column < top_column {
+                        return Err(layout_error(
+                            token,
+                            "line is indented less than the top-level declarations",
+                        ));
+                    }
+                    new_declaration =
+                        token.column == top_column && !CONTINUATION_TOKENS.contains(&token.text);
+                    new_declaration
+                }
+                None => false,
+            };
+            if aligned && !new_declaration {
+                prefix.push_str(" ; ");
+            }
+            if closed && !aligned && !continues_expression(token.text) {
+                return Err(layout_error(
+                    token,
+                    "indentation does not match any enclosing block",
+                ));
+            }
+        }
+
+        match token.text {
+            "in" => {
+                if let Some(Context::Implicit(_, "let")) = layout.last() {
+                    layout.pop();
+                    prefix.push_str(" } ");
+                }
+            }
+            "(" | "[" | "if" => brackets.push((token.text, layout.len())),
+            ")" | "]" | "," | "then" | "else" => {
+                let opening = match token.text {
+                    ")" => "(",
+                    "]" => "[",
+                    "," => "([",
+                    _ => "if",
+                };
+                if let Some((bracket, depth)) = brackets.last() {
+                    if opening.contains(bracket) {
+                        while layout.len() > *depth {
+                            if layout.pop() == Some(Context::Explicit) {
+                                return Err(layout_error(token, "unclosed explicit block"));
+                            }
+                            prefix.push_str(" } ");
+                        }
+                        if matches!(token.text, ")" | "]" | "else") {
+                            brackets.pop();
+                        }
+                    }
+                }
+            }
+            "{" => layout.push(Context::Explicit),
+            "}" => {
+                while let Some(Context::Implicit(_, _)) = layout.last() {
+                    layout.pop();
+                    prefix.push_str(" } ");
+                }
+                layout.pop();
+            }
+            "where" if in_header => in_header = false,
+            keyword if BLOCK_KEYWORDS.contains(&keyword) => block_keyword = Some(keyword),
+            _ => {}
+        }
+
+        out.push(&prefix, last_end, 0);
+        if new_declaration {
+            out.push(gap, last_end, gap.len());
+        } else {
+            out.push(&join_lines(gap), last_end, gap.len());
+        }
+        out.push(token.text, token.start, token.text.len());
+        last_end = token.end;
+    }
+
+    if block_keyword.is_some() {
+        out.push(" {}", last_end, 0);
+    }
+    for context in layout.iter().rev() {
+        if let Context::Implicit(_, _) = context {
+            out.push(" }", last_end, 0);
+        }
+    }
+    out.push(&source[last_end..], last_end, source.len() - last_end);
+    Ok(out)
+}
+
+/// Joins the lines of the whitespace between two tokens, dropping its comments, as a line comment
+/// would run on over the tokens after it
+fn join_lines(gap: &str) -> String {
+    let mut joined = String::with_capacity(gap.len());
+    let mut rest = gap;
+    while let Some(c) = rest.chars().next() {
+        if let Some(len) = comment_len(rest) {
+            joined.push(' ');
+            rest = &rest[len..];
+            continue;
+        }
+        joined.push(if c == '\n' { ' ' } else { c });
+        rest = &rest[c.len_utf8()..];
+    }
+    joined
+}
+
+/// The length of the comment `text` starts with, if any. A line comment ends before its newline,
+/// and block comments nest.
+fn comment_len(text: &str) -> Option<usize> {
+    if text.starts_with("--") {
+        let dashes = text.find(|c| c != '-').unwrap_or(text.len());
+        if text[dashes..].starts_with(is_symbol) {
+            return None;
+        }
+        return Some(text.find('\n').unwrap_or(text.len()));
+    }
+    if !text.starts_with("{-") {
+        return None;
+    }
+    let mut depth = 0;
+    let mut i = 0;
+    while i < text.len() {
+        if text[i..].starts_with("{-") {
+            depth += 1;
+            i += 2;
+        } else if text[i..].starts_with("-}") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return Some(i);
+            }
+        } else {
+            i += text[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    Some(text.len())
+}
+
+fn continues_expression(token: &str) -> bool {
+    CONTINUATION_TOKENS.contains(&token) || token.chars().all(is_symbol)
+}
+
+fn layout_error(token: &Token, message: &str) -> ParsingError {
+    ParsingError::LayoutError(token.line, token.column, message.to_string())
+}
+
+fn is_symbol(c: char) -> bool {
+    "!#$%&*+./<=>?@\\^|-~:".contains(c)
+}
+
+fn is_identifier(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '\''
+}
+
+/// Splits the source into the tokens relevant to the layout rule, with their positions
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let chars = source.char_indices().collect::<Vec<_>>();
+    let end_of = |i: usize| chars.get(i).map(|(pos, _)| *pos).unwrap_or(source.len());
+    let mut tokens = vec![];
+    let mut line = 1;
+    let mut column = 1;
+    let mut first_on_line = true;
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        match c {
+            '\n' => {
+                line += 1;
+                column = 1;
+                first_on_line = true;
+                i += 1;
+                continue;
+            }
+            ' ' | '\r' => {
+                column += 1;
+                i += 1;
+                continue;
+            }
+            '\t' => {
+                column += 8 - (column - 1) % 8;
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(len) = comment_len(&source[start..]) {
+            // Comments are whitespace, whose newlines still count for the layout
+            while i < chars.len() && chars[i].0 < start + len {
+                if chars[i].1 == '\n' {
+                    line += 1;
+                    column = 1;
+                    first_on_line = true;
+                } else {
+                    column += 1;
+                }
+                i += 1;
+            }
+            continue;
+        }
+        let mut j = i + 1;
+        match c {
+            '"' => {
+                while j < chars.len() && chars[j].1 != '"' && chars[j].1 != '\n' {
+                    j += if chars[j].1 == '\\' { 2 } else { 1 };
+                }
+                j += 1;
+            }
+            '\'' => {
+                while j < chars.len() && chars[j].1 != '\'' && chars[j].1 != '\n' {
+                    j += if chars[j].1 == '\\' { 2 } else { 1 };
+                }
+                j += 1;
+            }
+            c if is_identifier(c) => {
+                while j < chars.len() && is_identifier(chars[j].1) {
+                    j += 1;
+                }
+            }
+            c if is_symbol(c) => {
+                while j < chars.len() && is_symbol(chars[j].1) {
+                    j += 1;
+                }
+            }
+            _ => {}
+        }
+        let j = j.min(chars.len());
+        let end = end_of(j);
+        tokens.push(Token {
+            text: &source[start..end],
+            start,
+            end,
+            line,
+            column,
+            first_on_line,
+        });
+        column += j - i;
+        first_on_line = false;
+        i = j;
+    }
+    tokens
+}
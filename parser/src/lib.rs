@@ -1,16 +1,27 @@
-use ast::ast::Decl;
+use ast::ast::{Decl, Module};
 use error::ParsingError;
 use log::info;
+pub use modules::load;
 mod error;
+mod fixity;
+mod layout;
 pub(crate) mod macros;
+mod modules;
 pub(crate) mod parse;
 mod util;
 
+/// Parses the declarations of a source file, leaving out its module header and imports
 pub fn parse(source: &str) -> Result<Vec<Decl>, ParsingError> {
-    let program = parse::build_ast(source.to_string())?;
-    info!("Parsed program: ");
-    for decl in &program {
+    Ok(parse_module(source)?.program)
+}
+
+/// Parses a source file into a module, whose names are not yet resolved against its imports
+pub fn parse_module(source: &str) -> Result<Module, ParsingError> {
+    let layout = layout::resolve_layout(source)?;
+    let module = parse::build_module(&layout.text).map_err(|err| layout.locate(err, source))?;
+    info!("Parsed module {}: ", module.name);
+    for decl in &module.program {
         info!("{}", decl);
     }
-    Ok(program)
+    Ok(module)
 }
@@ -0,0 +1,325 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ast::ast::{Decl, Expr, Guard, ImportList, InfixOp, List, Module, Program, Rhs};
+
+use crate::error::ParsingError;
+use crate::parse::{rename_decl, rename_expr};
+use crate::parse_module;
+
+/// The values a module exports, by the name they are imported under, with the name they are
+/// resolved to
+type Exports = HashMap<String, String>;
+
+struct Loader {
+    /// The directories modules are looked up in
+    dirs: Vec<PathBuf>,
+    /// The modules loaded so far, each after the modules it imports
+    modules: Vec<Module>,
+    /// The modules being loaded, each imported by the one before it
+    stack: Vec<String>,
+}
+
+/// Loads the module in the file at `path` together with the modules it imports, which are looked
+/// up in the directory of the file and then in `search_path`, the module `A.B` in the file
+/// `A/B.hs`. Every module comes after the modules it imports, so the one at `path` comes last.
+///
+/// The names of the modules are resolved: the values defined by an imported module are qualified
+/// with its name, as in `A.B.f`, and every use of an imported value refers to it by that name.
+pub fn load(path: &Path, search_path: &[PathBuf]) -> Result<Vec<Module>, ParsingError> {
+    let mut dirs = vec![path.parent().map(Path::to_path_buf).unwrap_or_default()];
+    dirs.extend(search_path.iter().cloned());
+    let mut loader = Loader {
+        dirs,
+        modules: vec![],
+        stack: vec![],
+    };
+    loader.visit(read_module(path)?)?;
+    let count = loader.modules.len();
+    let mut exports = HashMap::new();
+    let mut modules = vec![];
+    for (i, module) in loader.modules.into_iter().enumerate() {
+        modules.push(resolve(module, i + 1 == count, &mut exports)?);
+    }
+    Ok(modules)
+}
+
+impl Loader {
+    fn visit(&mut self, module: Module) -> Result<(), ParsingError> {
+        self.stack.push(module.name.clone());
+        for import in &module.imports {
+            if let Some(start) = self.stack.iter().position(|name| *name == import.module) {
+                let mut cycle = self.stack[start..].to_vec();
+                cycle.push(import.module.clone());
+                return Err(ParsingError::ImportCycle(cycle.join(" -> ")));
+            }
+            if self.modules.iter().any(|m| m.name == import.module) {
+                continue;
+            }
+            let path = self
+                .find(&import.module)
+                .ok_or(ParsingError::ModuleNotFound(import.module.clone()))?;
+            let mut imported = read_module(&path)?;
+            // A file without a header holds the module it is imported as
+            match imported.name.as_str() {
+                "Main" => imported.name = import.module.clone(),
+                name if name != import.module => {
+                    return Err(ParsingError::WrongModuleName(
+                        path.display().to_string(),
+                        name.to_string(),
+                        import.module.clone(),
+                    ))
+                }
+                _ => {}
+            }
+            self.visit(imported)?;
+        }
+        self.stack.pop();
+        self.modules.push(module);
+        Ok(())
+    }
+
+    /// The file holding a module, in the first directory that has one
+    fn find(&self, name: &str) -> Option<PathBuf> {
+        let file = format!("{}.hs", name.replace('.', "/"));
+        self.dirs
+            .iter()
+            .map(|dir| dir.join(&file))
+            .find(|path| path.is_file())
+    }
+}
+
+fn read_module(path: &Path) -> Result<Module, ParsingError> {
+    let source = fs::read_to_string(path)
+        .map_err(|err| ParsingError::ReadError(path.display().to_string(), err.to_string()))?;
+    parse_module(&source)
+}
+
+/// Renames the values a module defines and uses to the names they are resolved to, and records
+/// what it exports. The values of the module that is run keep their names.
+fn resolve(
+    mut module: Module,
+    root: bool,
+    exports: &mut HashMap<String, Exports>,
+) -> Result<Module, ParsingError> {
+    let qualify = |name: &str| match root {
+        true => name.to_string(),
+        false => format!("{}.{}", module.name, name),
+    };
+    let own = module
+        .program
+        .iter()
+        .filter_map(|decl| match decl {
+            Decl::FunDecl(name, _, _) => Some(name.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    // Everything each name in scope may refer to. Values defined by the module shadow imported
+    // ones, while a name imported from two modules is ambiguous.
+    let mut scope: HashMap<String, Vec<String>> = HashMap::new();
+    for name in &own {
+        let resolved = qualify(name);
+        scope.insert(name.clone(), vec![resolved.clone()]);
+        scope.insert(format!("{}.{}", module.name, name), vec![resolved]);
+    }
+    for import in &module.imports {
+        let exported = &exports[&import.module];
+        let listed = match &import.names {
+            ImportList::All => &[][..],
+            ImportList::Only(names) | ImportList::Hiding(names) => &names[..],
+        };
+        if let Some(name) = listed.iter().find(|name| !exported.contains_key(*name)) {
+            return Err(ParsingError::NotExported(
+                import.module.clone(),
+                name.clone(),
+            ));
+        }
+        for (name, resolved) in exported {
+            let visible = match &import.names {
+                ImportList::All => true,
+                ImportList::Only(names) => names.contains(name),
+                ImportList::Hiding(names) => !names.contains(name),
+            };
+            if !visible {
+                continue;
+            }
+            if !import.qualified && !own.contains(name) {
+                scope
+                    .entry(name.clone())
+                    .or_default()
+                    .push(resolved.clone());
+            }
+            let qualified = format!("{}.{}", import.alias, name);
+            scope.entry(qualified).or_default().push(resolved.clone());
+        }
+    }
+    for resolved in scope.values_mut() {
+        resolved.sort();
+        resolved.dedup();
+    }
+
+    let mut program = module.program;
+    let used = free_names(&program);
+    for (name, resolved) in &scope {
+        match &resolved[..] {
+            [resolved] if resolved != name => program = rename_program(program, name, resolved),
+            [_] => {}
+            [first, second, ..] => {
+                // An ambiguous name is only an error where it is used
+                if used.contains(name) {
+                    return Err(ParsingError::AmbiguousName(
+                        name.clone(),
+                        first.clone(),
+                        second.clone(),
+                    ));
+                }
+            }
+            [] => unreachable!("Names in scope refer to something"),
+        }
+    }
+
+    let mut exported = Exports::new();
+    for name in module.exports.unwrap_or(own) {
+        match scope.get(&name).map(|resolved| &resolved[..]) {
+            Some([resolved]) => exported.insert(name, resolved.clone()),
+            _ => return Err(ParsingError::UnknownExport(module.name, name)),
+        };
+    }
+    module.exports = Some(exported.values().cloned().collect());
+    exports.insert(module.name.clone(), exported);
+    module.program = program;
+    Ok(module)
+}
+
+/// The names a program uses without binding them locally, which are the ones renaming a top-level
+/// value changes
+fn free_names(program: &[Decl]) -> HashSet<String> {
+    program.iter().flat_map(free_in_decl).collect()
+}
+
+fn free_in_decl(decl: &Decl) -> HashSet<String> {
+    match decl {
+        Decl::FunDecl(_, args, e) => without(free_in_expr(e), args.iter().cloned()),
+        Decl::ClassDecl(_, _, _, decls) | Decl::InstanceDecl(_, _, _, decls) => free_names(decls),
+        Decl::SExpr(e) => free_in_expr(e),
+        _ => HashSet::new(),
+    }
+}
+
+/// The free names of a `let` or `where` block and what it scopes over
+fn free_in_block(decls: &[Decl], mut free: HashSet<String>) -> HashSet<String> {
+    free.extend(free_names(decls));
+    let bound = decls.iter().filter_map(|decl| match decl {
+        Decl::FunDecl(name, _, _) => Some(name.clone()),
+        _ => None,
+    });
+    without(free, bound)
+}
+
+fn free_in_expr(expr: &Expr) -> HashSet<String> {
+    match expr {
+        Expr::Var(name) => HashSet::from([name.clone()]),
+        Expr::Lambda(arg, e) => without(free_in_expr(e), [arg.clone()]),
+        Expr::Let(decls, e) => free_in_block(decls, free_in_expr(e)),
+        Expr::Application(e1, e2) | Expr::BinOp(e1, _, e2) => {
+            [e1, e2].into_iter().flat_map(|e| free_in_expr(e)).collect()
+        }
+        Expr::If(a, b, c) => [a, b, c]
+            .into_iter()
+            .flat_map(|e| free_in_expr(e))
+            .collect(),
+        Expr::Case(e, cases) => {
+            let mut free = free_in_expr(e);
+            for (p, rhs) in cases {
+                free.extend(without(free_in_rhs(rhs), p.vars()));
+            }
+            free
+        }
+        Expr::Infix(first, rest) => {
+            let mut free = free_in_expr(first);
+            for (op, e) in rest {
+                if let InfixOp::Named(name) = op {
+                    free.insert(name.clone());
+                }
+                free.extend(free_in_expr(e));
+            }
+            free
+        }
+        Expr::Negate(e) | Expr::Annotated(e, _) => free_in_expr(e),
+        Expr::Tuple(es) => es.iter().flat_map(free_in_expr).collect(),
+        Expr::List(ls) => {
+            let mut free = HashSet::new();
+            let mut current = ls;
+            while let List::Some(e, rest) = current {
+                free.extend(free_in_expr(e));
+                current = rest;
+            }
+            free
+        }
+        Expr::Range(from, then, to) => [Some(from), then.as_ref(), to.as_ref()]
+            .into_iter()
+            .flatten()
+            .flat_map(|e| free_in_expr(e))
+            .collect(),
+        Expr::Literal(_) | Expr::Operator(_) | Expr::Constructor(_) => HashSet::new(),
+    }
+}
+
+fn free_in_rhs(rhs: &Rhs) -> HashSet<String> {
+    match rhs {
+        Rhs::Expr(e) => free_in_expr(e),
+        Rhs::Guarded(alternatives) => {
+            let mut free = HashSet::new();
+            for (guards, e) in alternatives {
+                // A pattern guard binds its variables in the guards after it and the body
+                let mut bound = HashSet::new();
+                for guard in guards {
+                    let (cond, vars) = match guard {
+                        Guard::Bool(cond) => (cond, vec![]),
+                        Guard::Pattern(p, cond) => (cond, p.vars()),
+                    };
+                    free.extend(without(free_in_expr(cond), bound.iter().cloned()));
+                    bound.extend(vars);
+                }
+                free.extend(without(free_in_expr(e), bound));
+            }
+            free
+        }
+        Rhs::Where(decls, rhs) => free_in_block(decls, free_in_rhs(rhs)),
+    }
+}
+
+fn without(mut names: HashSet<String>, bound: impl IntoIterator<Item = String>) -> HashSet<String> {
+    for name in bound {
+        names.remove(&name);
+    }
+    names
+}
+
+/// Renames the top-level value `old` and its uses in a program
+fn rename_program(program: Program, old: &String, new: &String) -> Program {
+    let rename = |decls: Vec<Decl>| {
+        decls
+            .into_iter()
+            .map(|decl| rename_decl(decl, old, new))
+            .collect()
+    };
+    program
+        .into_iter()
+        .map(|decl| match rename_decl(decl, old, new) {
+            Decl::FunDecl(name, args, e) if name == *old => Decl::FunDecl(new.clone(), args, e),
+            Decl::TypeSignature(name, context, t) if name == *old => {
+                Decl::TypeSignature(new.clone(), context, t)
+            }
+            Decl::ClassDecl(context, name, var, decls) => {
+                Decl::ClassDecl(context, name, var, rename(decls))
+            }
+            Decl::InstanceDecl(context, name, t, decls) => {
+                Decl::InstanceDecl(context, name, t, rename(decls))
+            }
+            Decl::SExpr(e) => Decl::SExpr(rename_expr(e, old, new)),
+            decl => decl,
+        })
+        .collect()
+}
@@ -1,10 +1,14 @@
 use std::collections::HashMap;
 
 use crate::error::ParsingError;
-use crate::error::ParsingError::GrammarError;
+use crate::error::ParsingError::{GrammarError, InvalidDoBlock, InvalidLiteral};
+use crate::fixity::{self, Associativity, Fixities, Fixity};
 use crate::info_parse;
-use crate::util::gen_arg_name;
-use ast::ast::{Decl, Expr, List, Literal, Op, Pattern, Program, Type};
+use crate::util::{gen_arg_name, gen_pattern_name, parse_integer, unescape};
+use ast::ast::{
+    Constraint, Decl, Expr, Guard, Import, ImportList, InfixOp, List, Literal, Module, Op, Pattern,
+    Rhs, Type,
+};
 use log::info;
 use pest::iterators::Pair;
 use pest::Parser;
@@ -14,14 +18,87 @@ use pest_derive::Parser;
 #[grammar = "./grammar.pest"]
 struct LexicalHaskell;
 
-pub fn build_ast(source: String) -> Result<Program, ParsingError> {
-    let pairs = LexicalHaskell::parse(Rule::program, &source)?;
+/// Builds the module a source file holds. Without a header, it is the module `Main` exporting
+/// everything.
+pub fn build_module(source: &str) -> Result<Module, ParsingError> {
+    let pairs = LexicalHaskell::parse(Rule::program, source)?;
     info!("Found {} decls", pairs.len());
-    let mut ast = vec![];
+    let mut module = Module {
+        name: "Main".to_string(),
+        exports: None,
+        imports: vec![],
+        program: vec![],
+    };
+    let mut fixities = Fixities::new();
     for pair in pairs {
-        ast.push(parse_decl(pair)?);
+        match pair.as_rule() {
+            Rule::module_header => {
+                let mut inner = pair.into_inner();
+                module.name = inner.next().ok_or(GrammarError)?.as_str().to_string();
+                module.exports = inner.next().map(parse_entities).transpose()?;
+            }
+            Rule::import_decl => module.imports.push(parse_import(pair)?),
+            Rule::fixity_decl => fixities.extend(parse_fixity(pair)?),
+            Rule::pattern_binding if strict_binding(&pair)?.is_some() => {
+                let (line, column) = pair.as_span().start_pos().line_col();
+                return Err(ParsingError::StrictTopLevel(line, column));
+            }
+            _ => module.program.extend(parse_decls(pair)?),
+        }
+    }
+    module.program = fixity::resolve_program(module.program, &fixities)?;
+    Ok(module)
+}
+
+fn parse_import(import: Pair<Rule>) -> Result<Import, ParsingError> {
+    info_parse!("Import", import);
+    let mut inner = import.into_inner().peekable();
+    let qualified = inner.next_if(|p| p.as_rule() == Rule::qualified).is_some();
+    let module = inner.next().ok_or(GrammarError)?.as_str().to_string();
+    let alias = match inner.next_if(|p| p.as_rule() == Rule::module_name) {
+        Some(alias) => alias.as_str().to_string(),
+        None => module.clone(),
+    };
+    let hiding = inner.next_if(|p| p.as_rule() == Rule::hiding).is_some();
+    let names = match (inner.next().map(parse_entities).transpose()?, hiding) {
+        (None, _) => ImportList::All,
+        (Some(names), false) => ImportList::Only(names),
+        (Some(names), true) => ImportList::Hiding(names),
+    };
+    Ok(Import {
+        module,
+        qualified,
+        alias,
+        names,
+    })
+}
+
+/// The values listed in an export or import list. Types, constructors and classes are always in
+/// scope, so they cannot be listed.
+fn parse_entities(entities: Pair<Rule>) -> Result<Vec<String>, ParsingError> {
+    let mut names = vec![];
+    // Types, constructors and classes are always in scope, so listing them changes nothing
+    for entity in entities.into_inner() {
+        if entity.as_rule() != Rule::type_entity {
+            names.push(parse_symname(entity)?);
+        }
+    }
+    Ok(names)
+}
+
+/// Parses a declaration, of which a pattern binding makes several
+fn parse_decls(decl: Pair<Rule>) -> Result<Vec<Decl>, ParsingError> {
+    match decl.as_rule() {
+        Rule::pattern_binding => {
+            let position = decl.as_span().start_pos().line_col();
+            let mut inner = decl.into_inner();
+            let pattern = parse_pattern(inner.next().ok_or(GrammarError)?)?;
+            let rhs = parse_rhs(inner.next().ok_or(GrammarError)?)?;
+            let whole = gen_pattern_name(&pattern.vars(), position);
+            Ok(pattern_binding(whole, pattern, rhs))
+        }
+        _ => Ok(vec![parse_decl(decl)?]),
     }
-    Ok(ast)
 }
 
 fn parse_decl(decl: Pair<Rule>) -> Result<Decl, ParsingError> {
@@ -30,8 +107,14 @@ fn parse_decl(decl: Pair<Rule>) -> Result<Decl, ParsingError> {
         Rule::type_decl => {
             let mut inner = decl.into_inner();
             let var = parse_symname(inner.next().ok_or(GrammarError)?)?;
-            let typ = parse_type(inner.next().ok_or(GrammarError)?)?;
-            Ok(Decl::TypeSignature(var, typ))
+            let mut next = inner.next().ok_or(GrammarError)?;
+            let mut context = vec![];
+            if next.as_rule() == Rule::context {
+                context = parse_context(next)?;
+                next = inner.next().ok_or(GrammarError)?;
+            }
+            let typ = parse_type(next)?;
+            Ok(Decl::TypeSignature(var, context, typ))
         }
         Rule::type_alias => {
             let mut inner = decl.into_inner();
@@ -39,57 +122,92 @@ fn parse_decl(decl: Pair<Rule>) -> Result<Decl, ParsingError> {
             let typ = parse_type(inner.next().ok_or(GrammarError)?)?;
             Ok(Decl::TypeAlias(var, typ))
         }
-        Rule::fun_decl => {
+        Rule::data_decl => {
             let mut inner = decl.into_inner();
+            let name = parse_symname(inner.next().ok_or(GrammarError)?)?;
+            let mut params = vec![];
+            let mut constructors = vec![];
+            for pair in inner {
+                match pair.as_rule() {
+                    Rule::var_name => params.push(parse_symname(pair)?),
+                    Rule::constructor => constructors.push(parse_constructor(pair)?),
+                    _ => return Err(GrammarError),
+                }
+            }
+            Ok(Decl::DataDecl(name, params, constructors))
+        }
+        Rule::class_decl => {
+            let mut inner = decl.into_inner().peekable();
+            let mut context = vec![];
+            if let Some(pair) = inner.next_if(|p| p.as_rule() == Rule::context) {
+                context = parse_context(pair)?;
+            }
+            let name = parse_symname(inner.next().ok_or(GrammarError)?)?;
+            let var = parse_symname(inner.next().ok_or(GrammarError)?)?;
+            let decls = inner.map(parse_decl).collect::<Result<_, _>>()?;
+            Ok(Decl::ClassDecl(context, name, var, decls))
+        }
+        Rule::instance_decl => {
+            let mut inner = decl.into_inner().peekable();
+            let mut context = vec![];
+            if let Some(pair) = inner.next_if(|p| p.as_rule() == Rule::context) {
+                context = parse_context(pair)?;
+            }
+            let name = parse_symname(inner.next().ok_or(GrammarError)?)?;
+            let typ = parse_type(inner.next().ok_or(GrammarError)?)?;
+            let decls = inner.map(parse_decl).collect::<Result<_, _>>()?;
+            Ok(Decl::InstanceDecl(context, name, typ, decls))
+        }
+        Rule::fun_decl => {
+            let mut inner = decl.into_inner().peekable();
             // We knwo that there must be a symname next based on the rule being a fun_decl
             let fun_name = parse_symname(inner.next().ok_or(GrammarError)?)?;
             let mut cases = vec![];
 
             while inner.peek().is_some() {
+                inner.next_if(|p| p.as_rule() == Rule::line_break);
+                inner.next_if(|p| p.as_rule() == Rule::same_name);
                 let patterns = parse_patterns(inner.next().ok_or(GrammarError)?)?;
-                let expr = parse_expr(inner.next().ok_or(GrammarError)?)?;
+                let rhs = parse_rhs(inner.next().ok_or(GrammarError)?)?;
                 let pattern = match &patterns[..] {
                     [] => None,
                     [p] => Some(p.clone()),
                     ps => Some(Pattern::FakeTuple(ps.to_vec())),
                 };
-                cases.push((pattern, expr));
+                cases.push((pattern, rhs));
             }
-            let cases = rename_cases(fun_name.clone(), cases);
-            return match &cases[..] {
-                [] => unreachable!(),
-                [(None, e)] => Ok(Decl::FunDecl(fun_name, vec![], e.clone())),
-                [(Some(Pattern::Var(name)), e)] => {
-                    Ok(Decl::FunDecl(fun_name, vec![name.clone()], e.clone()))
-                }
-                [(None, _), ..] => Err(ParsingError::MultipleDefinitions(fun_name)),
-                [(Some(Pattern::Tuple(_)), _), ..] => {
-                    let name = gen_arg_name(fun_name.clone(), 0);
-                    let cases = cases.into_iter().map(|(a, b)| (a.unwrap(), b)).collect();
-                    let fun_rhs = Expr::Case(Box::new(Expr::Var(name.clone())), cases);
-                    Ok(Decl::FunDecl(fun_name, vec![name], fun_rhs))
-                }
-                [(Some(Pattern::FakeTuple(ps)), _), ..] => {
-                    let cloned = fun_name.clone();
-                    let args = (0..ps.len())
-                        .into_iter()
-                        .map(|i| gen_arg_name(cloned.clone(), i));
-                    let es = args.clone().map(|name| Expr::Var(name)).collect();
-                    let cases = cases.into_iter().map(|(a, b)| (a.unwrap(), b)).collect();
-                    let fun_rhs = Expr::Case(Box::new(Expr::Tuple(es)), cases);
-                    Ok(Decl::FunDecl(fun_name, args.rev().collect(), fun_rhs))
-                }
-                _ => {
-                    let name = format!("{}:arg", fun_name.clone());
-                    let cases = cases.into_iter().map(|(a, b)| (a.unwrap(), b)).collect();
-                    let fun_rhs = Expr::Case(Box::new(Expr::Var(name.clone())), cases);
-                    Ok(Decl::FunDecl(fun_name, vec![name], fun_rhs))
+            build_fun_decl(fun_name, cases)
+        }
+        Rule::op_decl => {
+            let mut inner = decl
+                .into_inner()
+                .filter(|p| p.as_rule() != Rule::line_break);
+            let mut fun_name = None;
+            let mut cases = vec![];
+            while let Some(left) = inner.next() {
+                let operator = inner.next().ok_or(GrammarError)?;
+                if operator.as_rule() != Rule::same_op {
+                    fun_name = Some(operator_name(operator)?);
                 }
-            };
+                let left = parse_pattern(left)?;
+                let right = parse_pattern(inner.next().ok_or(GrammarError)?)?;
+                let rhs = parse_rhs(inner.next().ok_or(GrammarError)?)?;
+                cases.push((Some(Pattern::FakeTuple(vec![left, right])), rhs));
+            }
+            build_fun_decl(fun_name.ok_or(GrammarError)?, cases)
+        }
+        Rule::typed_binding => {
+            let mut inner = decl.into_inner();
+            let mut typed_var = inner.next().ok_or(GrammarError)?.into_inner();
+            let var = parse_symname(typed_var.next().ok_or(GrammarError)?)?;
+            let t = parse_type(typed_var.next().ok_or(GrammarError)?)?;
+            let rhs = parse_rhs(inner.next().ok_or(GrammarError)?)?;
+            let body = Expr::Annotated(Box::new(rhs_expr(rhs)), Box::new(t));
+            Ok(Decl::FunDecl(var, vec![], body))
         }
         Rule::paren_expr
         | Rule::expr
-        | Rule::io_expr
+        | Rule::fexpr
         | Rule::aexpr
         | Rule::application
         | Rule::number
@@ -97,19 +215,28 @@ fn parse_decl(decl: Pair<Rule>) -> Result<Decl, ParsingError> {
         | Rule::bool
         | Rule::string
         | Rule::type_name
+        | Rule::qualified_name
         | Rule::reserved
+        | Rule::unit
         | Rule::tuple_expr
         | Rule::cond
+        | Rule::case_expr
+        | Rule::do_block
         | Rule::let_in
         | Rule::lambda
+        | Rule::var_op
+        | Rule::left_section
+        | Rule::right_section
         | Rule::literal
         | Rule::var_name
         | Rule::list_expr
+        | Rule::list_comp
         | Rule::empty_list
         | Rule::open_range
         | Rule::open_step_range
         | Rule::closed_range
         | Rule::closed_step_range
+        | Rule::negation
         | Rule::infixop => {
             let expr = parse_expr(decl)?;
             Ok(Decl::SExpr(expr))
@@ -121,15 +248,110 @@ fn parse_decl(decl: Pair<Rule>) -> Result<Decl, ParsingError> {
     res
 }
 
+/// Builds a function out of its equations, matching the arguments against their patterns in a
+/// case expression if necessary
+fn build_fun_decl(
+    fun_name: String,
+    cases: Vec<(Option<Pattern>, Rhs)>,
+) -> Result<Decl, ParsingError> {
+    let arity = |pattern: &Option<Pattern>| match pattern {
+        None => 0,
+        Some(Pattern::FakeTuple(ps)) => ps.len(),
+        Some(_) => 1,
+    };
+    if cases
+        .iter()
+        .any(|(pattern, _)| arity(pattern) != arity(&cases[0].0))
+    {
+        return Err(ParsingError::VaryingArity(fun_name));
+    }
+    let cases = rename_cases(fun_name.clone(), cases);
+    match &cases[..] {
+        [] => unreachable!(),
+        [(None, rhs)] => Ok(Decl::FunDecl(fun_name, vec![], rhs_expr(rhs.clone()))),
+        [(Some(Pattern::Var(name)), rhs)] => Ok(Decl::FunDecl(
+            fun_name,
+            vec![name.clone()],
+            rhs_expr(rhs.clone()),
+        )),
+        [(None, _), ..] => Err(ParsingError::MultipleDefinitions(fun_name)),
+        [(Some(Pattern::Tuple(_)), _), ..] => {
+            let name = gen_arg_name(fun_name.clone(), 0);
+            let cases = cases.into_iter().map(|(a, b)| (a.unwrap(), b)).collect();
+            let fun_rhs = Expr::Case(Box::new(Expr::Var(name.clone())), cases);
+            Ok(Decl::FunDecl(fun_name, vec![name], fun_rhs))
+        }
+        [(Some(Pattern::FakeTuple(ps)), _), ..] => {
+            let cloned = fun_name.clone();
+            let args = (0..ps.len())
+                .into_iter()
+                .map(|i| gen_arg_name(cloned.clone(), i));
+            let es = args.clone().map(|name| Expr::Var(name)).collect();
+            let cases = cases.into_iter().map(|(a, b)| (a.unwrap(), b)).collect();
+            let fun_rhs = Expr::Case(Box::new(Expr::Tuple(es)), cases);
+            Ok(Decl::FunDecl(fun_name, args.rev().collect(), fun_rhs))
+        }
+        _ => {
+            let name = format!("{}:arg", fun_name.clone());
+            let cases = cases.into_iter().map(|(a, b)| (a.unwrap(), b)).collect();
+            let fun_rhs = Expr::Case(Box::new(Expr::Var(name.clone())), cases);
+            Ok(Decl::FunDecl(fun_name, vec![name], fun_rhs))
+        }
+    }
+}
+
 fn parse_expr(expr: Pair<Rule>) -> Result<Expr, ParsingError> {
     info_parse!("Expression", expr);
     let expr = match expr.as_rule() {
+        Rule::expr => {
+            let mut inner = expr.into_inner();
+            let e = parse_expr(inner.next().ok_or(GrammarError)?)?;
+            match inner.next() {
+                Some(t) => Ok(Expr::Annotated(Box::new(e), Box::new(parse_type(t)?))),
+                None => Ok(e),
+            }
+        }
         Rule::infixop => {
+            // The grammar nests chains of operators to the right, which are flattened here and
+            // grouped by fixity once all fixity declarations are known
+            let mut inner = expr.into_inner();
+            let first = parse_operand(inner.next().ok_or(GrammarError)?)?;
+            let mut rest = vec![];
+            while let Some(operator) = inner.next() {
+                let operator = parse_infix_op(operator)?;
+                let operand = inner.next().ok_or(GrammarError)?;
+                if operand.as_rule() == Rule::infixop {
+                    inner = operand.into_inner();
+                    let operand = parse_operand(inner.next().ok_or(GrammarError)?)?;
+                    rest.push((operator, operand));
+                } else {
+                    rest.push((operator, parse_operand(operand)?));
+                }
+            }
+            Ok(Expr::Infix(Box::new(first), rest))
+        }
+        Rule::negation => {
+            let e = parse_expr(expr.into_inner().next().ok_or(GrammarError)?)?;
+            Ok(fixity::negate(e))
+        }
+        Rule::var_op => {
+            let operator = parse_infix_op(expr.into_inner().next().ok_or(GrammarError)?)?;
+            Ok(fixity::operator_expr(operator))
+        }
+        Rule::left_section => {
             let mut inner = expr.into_inner();
-            let e1 = parse_expr(inner.next().ok_or(GrammarError)?)?;
-            let binop = parse_binop(inner.next().ok_or(GrammarError)?)?;
-            let e2 = parse_expr(inner.next().ok_or(GrammarError)?)?;
-            Ok(Expr::BinOp(Box::new(e1), binop, Box::new(e2)))
+            let e = parse_expr(inner.next().ok_or(GrammarError)?)?;
+            let operator = parse_infix_op(inner.next().ok_or(GrammarError)?)?;
+            let operator = fixity::operator_expr(operator);
+            Ok(Expr::Application(Box::new(operator), Box::new(e)))
+        }
+        Rule::right_section => {
+            let mut inner = expr.into_inner();
+            let operator = parse_infix_op(inner.next().ok_or(GrammarError)?)?;
+            let e = parse_expr(inner.next().ok_or(GrammarError)?)?;
+            let arg = "section:arg".to_string();
+            let body = fixity::apply(operator, Expr::Var(arg.clone()), e);
+            Ok(Expr::Lambda(arg, Box::new(body)))
         }
         Rule::application => {
             let inner = expr.into_inner();
@@ -158,11 +380,46 @@ fn parse_expr(expr: Pair<Rule>) -> Result<Expr, ParsingError> {
             let var = parse_symname(expr)?;
             Ok(Expr::Var(var))
         }
+        Rule::type_name => {
+            let name = parse_symname(expr)?;
+            Ok(Expr::Constructor(name))
+        }
+        // Constructors are in scope everywhere, so only the names of values keep their qualifier
+        Rule::qualified_name => match expr.as_str().rsplit_once('.') {
+            Some((_, name)) if name.starts_with(char::is_uppercase) => {
+                Ok(Expr::Constructor(name.to_string()))
+            }
+            _ => Ok(Expr::Var(expr.as_str().to_string())),
+        },
+        Rule::unit => Ok(Expr::Tuple(vec![])),
         Rule::tuple_expr => {
             let inner = expr.into_inner();
             let es: Vec<Expr> = inner.map(|p| parse_expr(p)).collect::<Result<_, _>>()?;
             Ok(Expr::Tuple(es))
         }
+        Rule::list_comp => {
+            let mut inner = expr.into_inner();
+            let e = parse_expr(inner.next().ok_or(GrammarError)?)?;
+            let qualifiers = inner
+                .map(|qualifier| match qualifier.as_rule() {
+                    Rule::local_let => {
+                        Ok(Qualifier::Let(parse_local_decls(qualifier.into_inner())?))
+                    }
+                    _ => Ok(Qualifier::Guard(parse_guard(qualifier)?)),
+                })
+                .collect::<Result<Vec<_>, ParsingError>>()?;
+            Ok(desugar_comprehension(e, qualifiers))
+        }
+        Rule::do_block => {
+            let mut statements = vec![];
+            for statement in expr.into_inner() {
+                statements.push(match statement.as_rule() {
+                    Rule::local_let => Qualifier::Let(parse_local_decls(statement.into_inner())?),
+                    _ => Qualifier::Guard(parse_guard(statement)?),
+                });
+            }
+            desugar_do(statements).ok_or(InvalidDoBlock)
+        }
         Rule::list_expr => {
             let inner = expr.into_inner();
             let es: Vec<Expr> = inner
@@ -176,43 +433,27 @@ fn parse_expr(expr: Pair<Rule>) -> Result<Expr, ParsingError> {
         }
         Rule::open_range => {
             let mut inner = expr.into_inner();
-            let first = parse_expr(inner.next().ok_or(GrammarError)?)?;
-            Ok(Expr::Range(
-                Box::new(first),
-                Box::new(Expr::Literal(Literal::Int(1))),
-                None,
-            ))
+            let from = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
+            Ok(Expr::Range(from, None, None))
         }
         Rule::open_step_range => {
             let mut inner = expr.into_inner();
-            let first = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
-            let second = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
-            Ok(Expr::Range(
-                first.clone(),
-                Box::new(Expr::BinOp(second, Op::Sub, first)),
-                None,
-            ))
+            let from = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
+            let then = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
+            Ok(Expr::Range(from, Some(then), None))
         }
         Rule::closed_range => {
             let mut inner = expr.into_inner();
-            let first = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
-            let last = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
-            Ok(Expr::Range(
-                first.clone(),
-                Box::new(Expr::Literal(Literal::Int(1))),
-                Some(last),
-            ))
+            let from = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
+            let to = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
+            Ok(Expr::Range(from, None, Some(to)))
         }
         Rule::closed_step_range => {
             let mut inner = expr.into_inner();
-            let first = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
-            let second = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
-            let last = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
-            Ok(Expr::Range(
-                first.clone(),
-                Box::new(Expr::BinOp(second, Op::Sub, first)),
-                Some(last),
-            ))
+            let from = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
+            let then = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
+            let to = Box::new(parse_expr(inner.next().ok_or(GrammarError)?)?);
+            Ok(Expr::Range(from, Some(then), Some(to)))
         }
         Rule::empty_list => Ok(Expr::List(List::Empty)),
         Rule::cond => {
@@ -223,44 +464,363 @@ fn parse_expr(expr: Pair<Rule>) -> Result<Expr, ParsingError> {
             let test = Box::new(es.pop().ok_or(GrammarError)?);
             Ok(Expr::If(test, then_expr, else_expr))
         }
-        Rule::lambda => {
+        Rule::case_expr => {
             let mut inner = expr.into_inner();
-            let var_name = parse_symname(inner.next().ok_or(GrammarError)?)?;
-            let expr = parse_expr(inner.next().ok_or(GrammarError)?)?;
-            Ok(Expr::Lambda(var_name, Box::new(expr)))
+            let scrutinee = parse_expr(inner.next().ok_or(GrammarError)?)?;
+            let mut alternatives = vec![];
+            for alternative in inner {
+                let mut inner = alternative.into_inner();
+                let pattern = parse_pattern(inner.next().ok_or(GrammarError)?)?;
+                let rhs = parse_rhs(inner.next().ok_or(GrammarError)?)?;
+                alternatives.push((pattern, rhs));
+            }
+            Ok(Expr::Case(Box::new(scrutinee), alternatives))
+        }
+        Rule::lambda => {
+            let mut inner = expr.into_inner().collect::<Vec<_>>();
+            let mut body = parse_expr(inner.pop().ok_or(GrammarError)?)?;
+            let mut patterns = vec![];
+            for pair in inner {
+                match pair.as_rule() {
+                    // An annotated argument is bound by matching its annotated value against it
+                    Rule::typed_var => {
+                        let mut inner = pair.into_inner();
+                        let var = parse_symname(inner.next().ok_or(GrammarError)?)?;
+                        let t = parse_type(inner.next().ok_or(GrammarError)?)?;
+                        let annotated =
+                            Expr::Annotated(Box::new(Expr::Var(var.clone())), Box::new(t));
+                        let alternative = (Pattern::Var(var.clone()), Rhs::Expr(body));
+                        body = Expr::Case(Box::new(annotated), vec![alternative]);
+                        patterns.push(Pattern::Var(var));
+                    }
+                    _ => patterns.push(parse_pattern(pair)?),
+                }
+            }
+            Ok(desugar_lambda(patterns, body))
         }
         Rule::let_in => {
-            let mut inner = expr.into_inner();
-            let var_name = parse_symname(inner.next().ok_or(GrammarError)?)?;
-            let expr1 = parse_expr(inner.next().ok_or(GrammarError)?)?;
-            let expr2 = parse_expr(inner.next().ok_or(GrammarError)?)?;
-            Ok(Expr::Let(var_name, Box::new(expr1), Box::new(expr2)))
+            let mut inner = expr.into_inner().collect::<Vec<_>>();
+            let body = parse_expr(inner.pop().ok_or(GrammarError)?)?;
+            let forced = strict_bindings(&inner)?;
+            let decls = parse_local_decls(inner)?;
+            Ok(Expr::Let(decls, Box::new(force_bindings(&forced, body))))
         }
         _ => Err(GrammarError),
     };
     return expr;
 }
 
-fn parse_binop(infixop: Pair<Rule>) -> Result<Op, ParsingError> {
-    info_parse!("Binary Operation", infixop);
-    return match infixop.as_str() {
-        "+" => Ok(Op::Add),
-        "-" => Ok(Op::Sub),
-        "*" => Ok(Op::Mul),
-        "/" => Ok(Op::Div),
-        "`mod`" => Ok(Op::Mod),
-        "==" => Ok(Op::Eq),
-        "/=" => Ok(Op::Neq),
-        "<" => Ok(Op::Lt),
-        ">" => Ok(Op::Gt),
-        "<=" => Ok(Op::Le),
-        ">=" => Ok(Op::Ge),
-        "&&" => Ok(Op::And),
-        "||" => Ok(Op::Or),
-        "++" => Ok(Op::Append),
-        ":" => Ok(Op::Cons),
-        _ => Err(GrammarError),
+fn parse_rhs(rhs: Pair<Rule>) -> Result<Rhs, ParsingError> {
+    info_parse!("Right-hand side", rhs);
+    let mut alternatives = vec![];
+    let mut result = None;
+    for pair in rhs.into_inner() {
+        match pair.as_rule() {
+            Rule::guarded_rhs | Rule::guarded_alt => {
+                let mut inner = pair.into_inner().collect::<Vec<_>>();
+                let body = parse_expr(inner.pop().ok_or(GrammarError)?)?;
+                let guards = inner
+                    .into_iter()
+                    .map(parse_guard)
+                    .collect::<Result<_, _>>()?;
+                alternatives.push((guards, body));
+            }
+            // The bindings of a where block scope over all guards
+            Rule::where_block => {
+                let inner = pair.into_inner().collect::<Vec<_>>();
+                let forced = strict_bindings(&inner)?;
+                let decls = parse_local_decls(inner)?;
+                let rhs = result.take().unwrap_or(Rhs::Guarded(alternatives.clone()));
+                return Ok(Rhs::Where(decls, Box::new(force_rhs(&forced, rhs))));
+            }
+            _ => result = Some(Rhs::Expr(parse_expr(pair)?)),
+        }
+    }
+    Ok(result.unwrap_or(Rhs::Guarded(alternatives)))
+}
+
+/// Parses the bindings and type signatures of a `let` or `where` block
+fn parse_local_decls<'a>(
+    pairs: impl IntoIterator<Item = Pair<'a, Rule>>,
+) -> Result<Vec<Decl>, ParsingError> {
+    let decls = pairs
+        .into_iter()
+        .map(parse_decls)
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    for (i, decl) in decls.iter().enumerate() {
+        if let Decl::FunDecl(name, _, _) = decl {
+            if decls[..i].iter().any(|other| binds(other, name)) {
+                return Err(ParsingError::MultipleDefinitions(name.clone()));
+            }
+        }
+    }
+    Ok(decls)
+}
+
+fn parse_guard(guard: Pair<Rule>) -> Result<Guard, ParsingError> {
+    info_parse!("Guard", guard);
+    match guard.as_rule() {
+        Rule::pattern_guard => {
+            let mut inner = guard.into_inner();
+            let pattern = parse_pattern(inner.next().ok_or(GrammarError)?)?;
+            let expr = parse_expr(inner.next().ok_or(GrammarError)?)?;
+            Ok(Guard::Pattern(pattern, expr))
+        }
+        _ => Ok(Guard::Bool(parse_expr(guard)?)),
+    }
+}
+
+/// Turns the right-hand side of a function with a single equation into its body. Guards are kept
+/// in a case expression matching anything.
+fn rhs_expr(rhs: Rhs) -> Expr {
+    match rhs {
+        Rhs::Expr(e) => e,
+        Rhs::Where(decls, rhs) if matches!(*rhs, Rhs::Expr(_)) => {
+            Expr::Let(decls, Box::new(rhs_expr(*rhs)))
+        }
+        rhs => Expr::Case(
+            Box::new(Expr::Tuple(vec![])),
+            vec![(Pattern::Wildcard, rhs)],
+        ),
+    }
+}
+
+/// The hidden names of the values of the banged pattern bindings among the declarations of a
+/// `let` or `where` block
+fn strict_bindings(decls: &[Pair<Rule>]) -> Result<Vec<String>, ParsingError> {
+    let mut names = vec![];
+    for decl in decls {
+        names.extend(strict_binding(decl)?);
+    }
+    Ok(names)
+}
+
+/// The hidden name of the value of a declaration, if it is a banged pattern binding
+fn strict_binding(decl: &Pair<Rule>) -> Result<Option<String>, ParsingError> {
+    if decl.as_rule() != Rule::pattern_binding {
+        return Ok(None);
+    }
+    let pattern = parse_pattern(decl.clone().into_inner().next().ok_or(GrammarError)?)?;
+    if !matches!(pattern, Pattern::Bang(_)) {
+        return Ok(None);
+    }
+    let position = decl.as_span().start_pos().line_col();
+    Ok(Some(gen_pattern_name(&pattern.vars(), position)))
+}
+
+/// Forces the values of banged bindings, given by their hidden names, before evaluating `body`
+fn force_bindings(forced: &[String], body: Expr) -> Expr {
+    forced.iter().rev().fold(body, |body, name| {
+        let alternative = (Pattern::Bang(Box::new(Pattern::Wildcard)), Rhs::Expr(body));
+        Expr::Case(Box::new(Expr::Var(name.clone())), vec![alternative])
+    })
+}
+
+/// Forces the values of the banged bindings of a `where` block before its right-hand side. When
+/// there are guards, the values are forced by a guard in front of each alternative, so that
+/// failing guards still fall through to the next equation.
+fn force_rhs(forced: &[String], rhs: Rhs) -> Rhs {
+    match rhs {
+        Rhs::Expr(e) => Rhs::Expr(force_bindings(forced, e)),
+        Rhs::Guarded(alternatives) => Rhs::Guarded(
+            alternatives
+                .into_iter()
+                .map(|(guards, e)| {
+                    let forcing = forced.iter().map(|name| {
+                        let bang = Pattern::Bang(Box::new(Pattern::Wildcard));
+                        Guard::Pattern(bang, Expr::Var(name.clone()))
+                    });
+                    (forcing.chain(guards).collect(), e)
+                })
+                .collect(),
+        ),
+        rhs => rhs,
+    }
+}
+
+/// Binds the whole value of a pattern binding to the hidden name `whole` and each variable of the
+/// pattern to a match against it. The match happens only once the variable is demanded, so a value
+/// which does not fit the pattern is an error only then.
+fn pattern_binding(whole: String, pattern: Pattern, rhs: Rhs) -> Vec<Decl> {
+    let mut decls = vec![Decl::FunDecl(whole.clone(), vec![], rhs_expr(rhs))];
+    for var in pattern.vars() {
+        let alternative = (pattern.clone(), Rhs::Expr(Expr::Var(var.clone())));
+        let body = Expr::Case(Box::new(Expr::Var(whole.clone())), vec![alternative]);
+        decls.push(Decl::FunDecl(var, vec![], body));
+    }
+    decls
+}
+
+/// Turns a lambda into nested single-argument lambdas. Unless all arguments are variables, they
+/// are matched against the patterns in a case expression, like the arguments of an equation.
+fn desugar_lambda(patterns: Vec<Pattern>, body: Expr) -> Expr {
+    let vars = patterns
+        .iter()
+        .map(|p| match p {
+            Pattern::Var(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+    if let Some(vars) = vars {
+        return vars
+            .into_iter()
+            .rev()
+            .fold(body, |acc, var| Expr::Lambda(var, Box::new(acc)));
+    }
+    let args = (0..patterns.len())
+        .map(|i| gen_arg_name("\\".to_string(), i))
+        .collect::<Vec<_>>();
+    let (scrutinee, pattern) = match &patterns[..] {
+        [p] => (Expr::Var(args[0].clone()), p.clone()),
+        _ => (
+            Expr::Tuple(args.iter().map(|arg| Expr::Var(arg.clone())).collect()),
+            Pattern::FakeTuple(patterns),
+        ),
     };
+    let case = Expr::Case(Box::new(scrutinee), vec![(pattern, Rhs::Expr(body))]);
+    args.into_iter()
+        .rev()
+        .fold(case, |acc, arg| Expr::Lambda(arg, Box::new(acc)))
+}
+
+/// A qualifier of a list comprehension, or a statement of a `do` block
+enum Qualifier {
+    /// A generator `p <- e` or a boolean guard, which is an action in a `do` block
+    Guard(Guard),
+    Let(Vec<Decl>),
+}
+
+/// Translates a list comprehension into local functions walking the generators, as in the
+/// Haskell report: `[e | p <- l, Q]` becomes a function returning `[e | Q]` on elements matching
+/// `p`, concatenated over `l`.
+fn desugar_comprehension(e: Expr, qualifiers: Vec<Qualifier>) -> Expr {
+    let mut qualifiers = qualifiers.into_iter();
+    let Some(qualifier) = qualifiers.next() else {
+        return Expr::List(List::Some(Box::new(e), Box::new(List::Empty)));
+    };
+    let rest = desugar_comprehension(e, qualifiers.collect());
+    match qualifier {
+        Qualifier::Guard(Guard::Bool(cond)) => Expr::If(
+            Box::new(cond),
+            Box::new(rest),
+            Box::new(Expr::List(List::Empty)),
+        ),
+        Qualifier::Let(decls) => Expr::Let(decls, Box::new(rest)),
+        Qualifier::Guard(Guard::Pattern(pattern, list)) => {
+            let go = "list:go".to_string();
+            let arg = "list:arg".to_string();
+            let tail = "list:rest".to_string();
+            let recurse = Expr::Application(
+                Box::new(Expr::Var(go.clone())),
+                Box::new(Expr::Var(tail.clone())),
+            );
+            let cons = |head| Pattern::List(Box::new(head), Box::new(Pattern::Var(tail.clone())));
+            let cases = vec![
+                (
+                    cons(pattern),
+                    Rhs::Expr(Expr::BinOp(
+                        Box::new(rest),
+                        Op::Append,
+                        Box::new(recurse.clone()),
+                    )),
+                ),
+                (cons(Pattern::Wildcard), Rhs::Expr(recurse)),
+                (Pattern::EmptyList, Rhs::Expr(Expr::List(List::Empty))),
+            ];
+            let body = Expr::Case(Box::new(Expr::Var(arg.clone())), cases);
+            let fun = Decl::FunDecl(go.clone(), vec![arg], body);
+            let call = Expr::Application(Box::new(Expr::Var(go)), Box::new(list));
+            Expr::Let(vec![fun], Box::new(call))
+        }
+    }
+}
+
+/// Translates a `do` block into applications of `>>=` and `>>`: `do { p <- e; S }` becomes
+/// `e >>= \p -> do { S }`, `do { e; S }` becomes `e >> do { S }` and `do { let ds; S }` becomes
+/// `let ds in do { S }`. There is none if the last statement is not an expression.
+fn desugar_do(mut statements: Vec<Qualifier>) -> Option<Expr> {
+    let Some(Qualifier::Guard(Guard::Bool(last))) = statements.pop() else {
+        return None;
+    };
+    let then = |op: &str, l: Expr, r: Expr| {
+        let op = Expr::Application(Box::new(Expr::Var(op.to_string())), Box::new(l));
+        Expr::Application(Box::new(op), Box::new(r))
+    };
+    let desugared = statements
+        .into_iter()
+        .rev()
+        .fold(last, |rest, statement| match statement {
+            Qualifier::Guard(Guard::Bool(e)) => then(">>", e, rest),
+            Qualifier::Guard(Guard::Pattern(p, e)) => then(">>=", e, desugar_lambda(vec![p], rest)),
+            Qualifier::Let(decls) => Expr::Let(decls, Box::new(rest)),
+        });
+    Some(desugared)
+}
+
+/// The name of an operator symbol, or of the identifier between backticks
+fn operator_name(operator: Pair<Rule>) -> Result<String, ParsingError> {
+    match operator.as_rule() {
+        Rule::backtick_op => parse_symname(operator.into_inner().next().ok_or(GrammarError)?),
+        _ => Ok(operator.as_str().to_string()),
+    }
+}
+
+fn parse_infix_op(operator: Pair<Rule>) -> Result<InfixOp, ParsingError> {
+    info_parse!("Infix Operator", operator);
+    let name = operator_name(operator)?;
+    let builtin = match name.as_str() {
+        "+" => Op::Add,
+        "-" => Op::Sub,
+        "*" => Op::Mul,
+        "/" => Op::Div,
+        "mod" => Op::Mod,
+        "==" => Op::Eq,
+        "/=" => Op::Neq,
+        "<" => Op::Lt,
+        ">" => Op::Gt,
+        "<=" => Op::Le,
+        ">=" => Op::Ge,
+        "&&" => Op::And,
+        "||" => Op::Or,
+        "++" => Op::Append,
+        ":" => Op::Cons,
+        _ => return Ok(InfixOp::Named(name)),
+    };
+    Ok(InfixOp::Builtin(builtin))
+}
+
+fn parse_fixity(decl: Pair<Rule>) -> Result<Vec<(String, Fixity)>, ParsingError> {
+    info_parse!("Fixity Declaration", decl);
+    let mut inner = decl.into_inner().peekable();
+    let associativity = match inner.next().ok_or(GrammarError)?.as_str() {
+        "infixl" => Associativity::Left,
+        "infixr" => Associativity::Right,
+        _ => Associativity::None,
+    };
+    let precedence = match inner.next_if(|p| p.as_rule() == Rule::precedence) {
+        Some(pair) => pair.as_str().parse().map_err(|_| GrammarError)?,
+        None => 9,
+    };
+    let fixity = Fixity {
+        associativity,
+        precedence,
+    };
+    inner
+        .map(|operator| Ok((fixity::key(&parse_infix_op(operator)?), fixity)))
+        .collect()
+}
+
+/// Parses an operand of an infix expression, where a negation is only grouped once the fixities
+/// of the surrounding operators are known
+fn parse_operand(operand: Pair<Rule>) -> Result<Expr, ParsingError> {
+    match operand.as_rule() {
+        Rule::negation => {
+            let e = parse_expr(operand.into_inner().next().ok_or(GrammarError)?)?;
+            Ok(Expr::Negate(Box::new(e)))
+        }
+        _ => parse_expr(operand),
+    }
 }
 
 fn parse_patterns(patterns: Pair<Rule>) -> Result<Vec<Pattern>, ParsingError> {
@@ -274,25 +834,53 @@ fn parse_pattern(pattern: Pair<Rule>) -> Result<Pattern, ParsingError> {
     return match pattern.as_rule() {
         Rule::wildcard => Ok(Pattern::Wildcard),
         Rule::empty_list => Ok(Pattern::EmptyList),
-        Rule::number | Rule::char | Rule::bool | Rule::string => {
+        Rule::number | Rule::neg_literal | Rule::char | Rule::bool | Rule::string => {
             Ok(Pattern::Literal(parse_literal(pattern)?))
         }
         Rule::var_name => {
             let name = parse_symname(pattern)?;
             Ok(Pattern::Var(name))
         }
+        Rule::type_name => {
+            let name = parse_symname(pattern)?;
+            Ok(Pattern::Constructor(name, vec![]))
+        }
+        Rule::con_pattern => {
+            let mut inner = pattern.into_inner();
+            let name = parse_symname(inner.next().ok_or(GrammarError)?)?;
+            let ps: Vec<Pattern> = inner.map(|p| parse_pattern(p)).collect::<Result<_, _>>()?;
+            Ok(Pattern::Constructor(name, ps))
+        }
         Rule::list_pattern => {
             let mut inner = pattern.into_inner();
-            let len = inner.len();
-            let head = inner.next().ok_or(ParsingError::GrammarError)?;
-            if len == 1 {
-                return parse_pattern(head);
-            }
-            let p1 = parse_pattern(head)?;
-            let tail = inner.next().ok_or(ParsingError::GrammarError)?;
-            let p2 = parse_pattern(tail)?;
+            let p1 = parse_pattern(inner.next().ok_or(GrammarError)?)?;
+            let p2 = parse_pattern(inner.next().ok_or(GrammarError)?)?;
             Ok(Pattern::List(Box::new(p1), Box::new(p2)))
         }
+        // `[p1, p2]` is short for `p1 : p2 : []`
+        Rule::elems_pattern => {
+            let ps = pattern
+                .into_inner()
+                .map(parse_pattern)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(ps.into_iter().rev().fold(Pattern::EmptyList, |acc, p| {
+                Pattern::List(Box::new(p), Box::new(acc))
+            }))
+        }
+        Rule::bang_pattern => {
+            let p = parse_pattern(pattern.into_inner().last().ok_or(GrammarError)?)?;
+            Ok(Pattern::Bang(Box::new(p)))
+        }
+        Rule::lazy_pattern => {
+            let p = parse_pattern(pattern.into_inner().next().ok_or(GrammarError)?)?;
+            Ok(Pattern::Lazy(Box::new(p)))
+        }
+        Rule::as_pattern => {
+            let mut inner = pattern.into_inner();
+            let name = parse_symname(inner.next().ok_or(GrammarError)?)?;
+            let p = parse_pattern(inner.next().ok_or(GrammarError)?)?;
+            Ok(Pattern::As(name, Box::new(p)))
+        }
         Rule::tuple_pattern => {
             let inner = pattern.into_inner();
             let ps: Vec<Pattern> = inner.map(|p| parse_pattern(p)).collect::<Result<_, _>>()?;
@@ -307,14 +895,27 @@ fn parse_literal(literal: Pair<Rule>) -> Result<Literal, ParsingError> {
     return match literal.as_rule() {
         Rule::number => {
             let num = literal.as_str();
-            if let Ok(val) = num.parse() {
-                return Ok(Literal::Int(val));
+            let hexadecimal = num.starts_with("0x") || num.starts_with("0X");
+            if !hexadecimal && num.contains(['.', 'e', 'E']) {
+                return num.parse().map(Literal::Double).or(Err(GrammarError));
+            }
+            parse_integer(num).ok_or(InvalidLiteral(num.to_string()))
+        }
+        Rule::neg_literal => {
+            match parse_literal(literal.into_inner().next().ok_or(GrammarError)?)? {
+                Literal::Int(val) => Ok(Literal::Int(-val)),
+                Literal::Integer(val) => Ok(Literal::Integer(-val)),
+                Literal::Double(val) => Ok(Literal::Double(-val)),
+                _ => Err(GrammarError),
             }
-            Err(GrammarError)
         }
         Rule::char => {
-            let char = literal.as_str().chars().nth(1).ok_or(GrammarError)?;
-            Ok(Literal::Char(char))
+            let s = literal.as_str();
+            let chars = unescape(&s[1..s.len() - 1]).map(|s| s.chars().collect::<Vec<_>>());
+            match chars.as_deref() {
+                Some([c]) => Ok(Literal::Char(*c)),
+                _ => Err(InvalidLiteral(s.to_string())),
+            }
         }
         Rule::bool => {
             let boolean = literal.as_str();
@@ -326,8 +927,9 @@ fn parse_literal(literal: Pair<Rule>) -> Result<Literal, ParsingError> {
         }
         Rule::string => {
             let s = literal.as_str();
-            let s = &s[1..s.len() - 1]; // Remove '"'
-            Ok(Literal::String(s.to_string()))
+            let body = &s[1..s.len() - 1]; // Remove '"'
+            let body = unescape(body).ok_or(InvalidLiteral(s.to_string()))?;
+            Ok(Literal::String(body))
         }
         _ => Err(GrammarError),
     };
@@ -340,7 +942,9 @@ fn parse_type(atype: Pair<Rule>) -> Result<Type, ParsingError> {
         Rule::type_name => {
             let name = parse_symname(atype)?;
             Ok(match name.as_str() {
-                "Int" | "Integer" => Type::Int,
+                "Int" => Type::Int,
+                "Integer" => Type::Integer,
+                "Double" => Type::Double,
                 "Bool" => Type::Bool,
                 "Char" => Type::Char,
                 "String" => Type::String,
@@ -356,11 +960,21 @@ fn parse_type(atype: Pair<Rule>) -> Result<Type, ParsingError> {
                 .reduce(|acc, arg| Type::Function(Box::new(arg), Box::new(acc)))
                 .ok_or(GrammarError)
         }
+        Rule::applied_type => {
+            let mut inner = atype.into_inner();
+            let name = parse_symname(inner.next().ok_or(GrammarError)?)?;
+            let args: Vec<Type> = inner.map(|p| parse_type(p)).collect::<Result<_, _>>()?;
+            match (name.as_str(), &args[..]) {
+                ("IO", [t]) => Ok(Type::IO(Box::new(t.clone()))),
+                _ => Ok(Type::Constructor(name, args)),
+            }
+        }
         Rule::paren_type => {
             let mut inner = atype.into_inner();
             let t = parse_type(inner.next().ok_or(GrammarError)?)?;
             Ok(t)
         }
+        Rule::unit_type => Ok(Type::Tuple(vec![])),
         Rule::tuple_type => {
             let inner = atype.into_inner();
             let es: Vec<Type> = inner.map(|p| parse_type(p)).collect::<Result<_, _>>()?;
@@ -377,24 +991,49 @@ fn parse_type(atype: Pair<Rule>) -> Result<Type, ParsingError> {
     };
 }
 
+fn parse_constructor(constructor: Pair<Rule>) -> Result<(String, Vec<Type>), ParsingError> {
+    info_parse!("Constructor", constructor);
+    let mut inner = constructor.into_inner();
+    let name = parse_symname(inner.next().ok_or(GrammarError)?)?;
+    let fields: Vec<Type> = inner.map(|p| parse_type(p)).collect::<Result<_, _>>()?;
+    Ok((name, fields))
+}
+
+fn parse_context(context: Pair<Rule>) -> Result<Vec<Constraint>, ParsingError> {
+    info_parse!("Context", context);
+    context
+        .into_inner()
+        .map(|constraint| {
+            let mut inner = constraint.into_inner();
+            let class = parse_symname(inner.next().ok_or(GrammarError)?)?;
+            let var = parse_symname(inner.next().ok_or(GrammarError)?)?;
+            Ok((class, var))
+        })
+        .collect()
+}
+
 fn parse_symname(name: Pair<Rule>) -> Result<String, ParsingError> {
     info_parse!("Symbol name", name);
+    if name.as_rule() == Rule::var_op {
+        let op = name.into_inner().next().ok_or(GrammarError)?;
+        return Ok(op.as_str().to_string());
+    }
     let name = name.as_str();
     return Ok(name.to_string());
 }
 
 fn rename_cases(
     fun_name: String,
-    cases: Vec<(Option<Pattern>, Expr)>,
-) -> Vec<(Option<Pattern>, Expr)> {
+    cases: Vec<(Option<Pattern>, Rhs)>,
+) -> Vec<(Option<Pattern>, Rhs)> {
     cases
         .into_iter()
         .map(|case| merge_var_bindings(fun_name.clone(), case))
         .collect()
 }
 
-fn merge_var_bindings(fun_name: String, case: (Option<Pattern>, Expr)) -> (Option<Pattern>, Expr) {
-    let (pattern, expr) = case;
+fn merge_var_bindings(fun_name: String, case: (Option<Pattern>, Rhs)) -> (Option<Pattern>, Rhs) {
+    let (pattern, rhs) = case;
     match pattern {
         Some(Pattern::FakeTuple(ps)) => {
             info!(
@@ -414,7 +1053,7 @@ fn merge_var_bindings(fun_name: String, case: (Option<Pattern>, Expr)) -> (Optio
                 })
                 .flatten()
                 .collect();
-            let expr = rename_vars(&renamings, expr);
+            let rhs = rename_vars(&renamings, rhs);
             let pattern = Pattern::FakeTuple(
                 ps.into_iter()
                     .map(|p| match p {
@@ -423,19 +1062,19 @@ fn merge_var_bindings(fun_name: String, case: (Option<Pattern>, Expr)) -> (Optio
                     })
                     .collect(),
             );
-            (Some(pattern), expr)
+            (Some(pattern), rhs)
         }
-        case => (case, expr),
+        case => (case, rhs),
     }
 }
 
-fn rename_vars(renamings: &HashMap<String, String>, expr: Expr) -> Expr {
+fn rename_vars(renamings: &HashMap<String, String>, rhs: Rhs) -> Rhs {
     renamings
         .iter()
-        .fold(expr, |acc, (old, new)| rename_expr(acc, old, new))
+        .fold(rhs, |acc, (old, new)| rename_rhs(acc, old, new))
 }
 
-fn rename_expr(expr: Expr, old: &String, new: &String) -> Expr {
+pub(crate) fn rename_expr(expr: Expr, old: &String, new: &String) -> Expr {
     match expr {
         Expr::Var(name) => {
             return if name.eq(old) {
@@ -451,14 +1090,16 @@ fn rename_expr(expr: Expr, old: &String, new: &String) -> Expr {
                 Expr::Lambda(arg, Box::new(rename_expr(*expr, old, new)))
             }
         }
-        Expr::Let(arg, expr1, expr2) => {
-            if arg.eq(old) {
-                Expr::Let(arg, expr1, expr2)
+        Expr::Let(decls, expr) => {
+            if decls.iter().any(|decl| binds(decl, old)) {
+                Expr::Let(decls, expr)
             } else {
                 Expr::Let(
-                    arg,
-                    Box::new(rename_expr(*expr1, old, new)),
-                    Box::new(rename_expr(*expr2, old, new)),
+                    decls
+                        .into_iter()
+                        .map(|decl| rename_decl(decl, old, new))
+                        .collect(),
+                    Box::new(rename_expr(*expr, old, new)),
                 )
             }
         }
@@ -483,15 +1124,43 @@ fn rename_expr(expr: Expr, old: &String, new: &String) -> Expr {
             op,
             Box::new(rename_expr(*r, old, new)),
         ),
+        Expr::Infix(first, rest) => Expr::Infix(
+            Box::new(rename_expr(*first, old, new)),
+            rest.into_iter()
+                .map(|(op, e)| match op {
+                    InfixOp::Named(name) if name.eq(old) => (InfixOp::Named(new.clone()), e),
+                    op => (op, e),
+                })
+                .map(|(op, e)| (op, rename_expr(e, old, new)))
+                .collect(),
+        ),
+        Expr::Negate(e) => Expr::Negate(Box::new(rename_expr(*e, old, new))),
+        Expr::Annotated(e, t) => Expr::Annotated(Box::new(rename_expr(*e, old, new)), t),
         Expr::Tuple(es) => Expr::Tuple(es.into_iter().map(|e| rename_expr(e, old, new)).collect()),
         Expr::List(ls) => Expr::List(rename_list(ls, old, new)),
         Expr::Literal(l) => Expr::Literal(l),
-        Expr::Range(start, step, stop) => {
-            Expr::Range(Box::new(rename_expr(*start, old, new)), step, stop)
+        Expr::Operator(op) => Expr::Operator(op),
+        Expr::Constructor(name) => Expr::Constructor(name),
+        Expr::Range(from, then, to) => {
+            let rename = |e: Box<Expr>| Box::new(rename_expr(*e, old, new));
+            Expr::Range(rename(from), then.map(rename), to.map(rename))
         }
     }
 }
 
+pub(crate) fn rename_decl(decl: Decl, old: &String, new: &String) -> Decl {
+    match decl {
+        Decl::FunDecl(name, args, expr) if !args.contains(old) => {
+            Decl::FunDecl(name, args, rename_expr(expr, old, new))
+        }
+        decl => decl,
+    }
+}
+
+fn binds(decl: &Decl, old: &String) -> bool {
+    matches!(decl, Decl::FunDecl(name, _, _) if name == old)
+}
+
 fn rename_list(ls: List<Expr>, old: &String, new: &String) -> List<Expr> {
     match ls {
         List::Some(e, es) => List::Some(
@@ -502,19 +1171,67 @@ fn rename_list(ls: List<Expr>, old: &String, new: &String) -> List<Expr> {
     }
 }
 
-fn rename_case(p: Pattern, e: Expr, old: &String, new: &String) -> (Pattern, Expr) {
+fn rename_case(p: Pattern, rhs: Rhs, old: &String, new: &String) -> (Pattern, Rhs) {
     if is_bound(&p, &old) {
-        return (p, e);
+        return (p, rhs);
+    }
+    return (p, rename_rhs(rhs, old, new));
+}
+
+fn rename_rhs(rhs: Rhs, old: &String, new: &String) -> Rhs {
+    match rhs {
+        Rhs::Expr(e) => Rhs::Expr(rename_expr(e, old, new)),
+        Rhs::Guarded(alternatives) => Rhs::Guarded(
+            alternatives
+                .into_iter()
+                .map(|(guards, e)| rename_guards(guards, e, old, new))
+                .collect(),
+        ),
+        Rhs::Where(decls, rhs) => {
+            if decls.iter().any(|decl| binds(decl, old)) {
+                Rhs::Where(decls, rhs)
+            } else {
+                Rhs::Where(
+                    decls
+                        .into_iter()
+                        .map(|decl| rename_decl(decl, old, new))
+                        .collect(),
+                    Box::new(rename_rhs(*rhs, old, new)),
+                )
+            }
+        }
+    }
+}
+
+/// Renames in guards up to the first pattern guard binding `old`, which shadows it from there on
+fn rename_guards(guards: Vec<Guard>, e: Expr, old: &String, new: &String) -> (Vec<Guard>, Expr) {
+    let mut shadowed = false;
+    let mut renamed = vec![];
+    for guard in guards {
+        renamed.push(match guard {
+            guard if shadowed => guard,
+            Guard::Bool(cond) => Guard::Bool(rename_expr(cond, old, new)),
+            Guard::Pattern(p, cond) => {
+                shadowed = is_bound(&p, old);
+                Guard::Pattern(p, rename_expr(cond, old, new))
+            }
+        });
+    }
+    match shadowed {
+        true => (renamed, e),
+        false => (renamed, rename_expr(e, old, new)),
     }
-    return (p, rename_expr(e, old, new));
 }
 
 fn is_bound(p: &Pattern, old: &String) -> bool {
     match p {
         Pattern::Var(name) => name.eq(old),
+        Pattern::As(name, p) => name.eq(old) || is_bound(p, old),
+        Pattern::Bang(p) | Pattern::Lazy(p) => is_bound(p, old),
         Pattern::Tuple(ps) => ps.into_iter().any(|p| is_bound(p, old)),
         Pattern::FakeTuple(ps) => ps.into_iter().any(|p| is_bound(p, old)),
         Pattern::List(p1, p2) => is_bound(p1, old) || is_bound(p2, old),
+        Pattern::Constructor(_, ps) => ps.into_iter().any(|p| is_bound(p, old)),
         Pattern::Literal(_) => false,
         Pattern::Wildcard => false,
         Pattern::EmptyList => false,
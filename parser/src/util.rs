@@ -1,3 +1,94 @@
+use ast::ast::Literal;
+use ast::display::ASCII_NAMES;
+use num_bigint::BigInt;
+
 pub fn gen_arg_name(fun_name: String, n: usize) -> String {
     format!("{}:arg{}", fun_name, n)
 }
+
+/// The name of the value a pattern binding takes apart, made of the variables it binds or, if it
+/// binds none, of the line and column it starts at
+pub fn gen_pattern_name(vars: &[String], (line, column): (usize, usize)) -> String {
+    match vars {
+        [] => format!("pat:{}:{}", line, column),
+        vars => format!("pat:{}", vars.join(":")),
+    }
+}
+
+/// Decodes the escapes in the body of a character or string literal, which is `None` if one of
+/// them is invalid
+pub fn unescape(literal: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut rest = literal;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let (c, after) = read_escape(rest)?;
+        result.extend(c);
+        rest = after;
+    }
+    Some(result)
+}
+
+/// Decodes the escape at the start of `escape`, the text after a backslash, into the character it
+/// stands for, if any, and the text after it
+fn read_escape(escape: &str) -> Option<(Option<char>, &str)> {
+    let first = escape.chars().next()?;
+    let after = &escape[first.len_utf8()..];
+    let c = match first {
+        'a' => '\x07',
+        'b' => '\x08',
+        'f' => '\x0c',
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'v' => '\x0b',
+        '\\' | '"' | '\'' => first,
+        // The empty escape, which ends an escape a digit or `H` would otherwise extend
+        '&' => return Some((None, after)),
+        '^' => {
+            let control = after.chars().next().filter(|c| ('@'..='_').contains(c))?;
+            return Some((Some(char::from(control as u8 - b'@')), &after[1..]));
+        }
+        'x' => return read_code(after, 16),
+        'o' => return read_code(after, 8),
+        '0'..='9' => return read_code(escape, 10),
+        _ => {
+            // The longest name is read, so `\SOH` is not `\SO` followed by `H`
+            let (code, name) = ASCII_NAMES
+                .iter()
+                .enumerate()
+                .chain([(0x7f, &"DEL")])
+                .filter(|(_, name)| escape.starts_with(*name))
+                .max_by_key(|(_, name)| name.len())?;
+            return Some((Some(char::from(code as u8)), &escape[name.len()..]));
+        }
+    };
+    Some((Some(c), after))
+}
+
+/// Decodes a numeric escape in base `radix`
+fn read_code(digits: &str, radix: u32) -> Option<(Option<char>, &str)> {
+    let end = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    let code = u32::from_str_radix(&digits[..end], radix).ok()?;
+    Some((Some(char::from_u32(code)?), &digits[end..]))
+}
+
+/// Reads an integer literal, which may be hexadecimal, octal or binary
+pub fn parse_integer(literal: &str) -> Option<Literal> {
+    let (radix, digits) = match literal.get(..2).map(str::to_ascii_lowercase).as_deref() {
+        Some("0x") => (16, &literal[2..]),
+        Some("0o") => (8, &literal[2..]),
+        Some("0b") => (2, &literal[2..]),
+        _ => (10, literal),
+    };
+    if let Ok(n) = i64::from_str_radix(digits, radix) {
+        return Some(Literal::Int(n));
+    }
+    BigInt::parse_bytes(digits.as_bytes(), radix).map(Literal::Integer)
+}
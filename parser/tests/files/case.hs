@@ -0,0 +1,43 @@
+data Maybe' a = Just a | Nothing
+
+data Shape = Circle Int | Rect Int Int
+
+describe s = case s of
+  Circle 0 -> "dot"
+  Circle r | r > 10 -> "big circle"
+           | otherwise -> "circle"
+  Rect w h
+    | w == h -> "square"
+  _ -> "shape"
+
+classify n = case n of { 0 -> "zero"; -1 -> "minus one"; _ -> "other" }
+
+firstTwo xs = case xs of
+  (a:b:rest) -> a + b
+  (x:[]) -> x
+  [] -> 0
+
+sumPair p = case p of (a, b) -> a + b where { unused = 1 }
+
+nested m = case m of
+  Just (x, (Just y)) -> case x of
+    0 -> y
+    _ -> x * y
+  _ -> 0
+
+describe (Circle 0)
+describe (Circle 20)
+describe (Circle 3)
+describe (Rect 2 2)
+describe (Rect 2 3)
+classify 0
+classify (-1)
+classify 5
+firstTwo [1, 2, 3]
+firstTwo [7]
+sumPair (3, 4)
+nested (Just (2, Just 5))
+nested (Just (0, Just 5))
+nested Nothing
+case 3 of 3 -> True; _ -> False
+(case 1 of { 1 -> 10 }) + 1
@@ -0,0 +1,6 @@
+class Container f where { empty :: f ; insert :: Int -> f -> f }
+class Eq a => Ord a where { (<) :: a -> a -> Bool ; (<=) :: a -> a -> Bool ; (<=) x y = not (y < x) }
+instance Eq a => Eq [a] where { (==) [] [] = True ; (==) (x:xs) (y:ys) = (x == y) && (xs == ys) ; (==) xs ys = False }
+instance Show Bool where { show True = "True" ; show False = "False" }
+elem :: Eq a => a -> [a] -> Bool
+lookup :: (Eq a, Show b) => a -> [(a, b)] -> String
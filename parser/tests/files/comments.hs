@@ -0,0 +1,27 @@
+{- A module header
+   {- with a nested comment -}
+   spanning lines -}
+
+-- | The factorial
+fact 0 = 1 -- base case
+fact n = n * fact (n - 1) --- recursive case
+
+infixr 5 -->
+(-->) :: Int -> Int -> Int
+x --> y = x + y -- not an operator
+
+total = 1 -- one
+  + 2 {- two -} + 3
+  -- a comment line inside the expression
+  + 4
+
+area r = pi' * r * r
+  where -- the constant
+    pi' = 3 -- roughly
+    {- done -}
+
+fact 5
+1 --> 2
+total
+area 2 {- inline -} + 1
+--the end
@@ -0,0 +1,5 @@
+evens xs = [x | x <- xs, x `mod` 2 == 0]
+pairs = [(x, y) | x <- [1, 2], y <- "ab"]
+squares = [y | x <- [1..], let y = x * x, y > 3]
+justs xs = [x | Just x <- xs]
+nested = [[(a, b) | b <- [1..3], a < b] | a <- [1..3]]
@@ -0,0 +1,12 @@
+data Color = Red | Green | Blue
+data Tree a = Leaf | Node (Tree a) a (Tree a)
+data Pair a b = Pair a b
+
+size :: Tree a -> Int
+size Leaf = 0
+size (Node l _ r) = size l + 1 + size r
+
+swap :: Pair a b -> Pair b a
+swap (Pair x y) = Pair y x
+
+size (Node Leaf Red Leaf)
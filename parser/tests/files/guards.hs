@@ -0,0 +1,17 @@
+sign x
+  | x < 0 = 0 - 1
+  | x == 0 = 0
+  | otherwise = 1
+
+classify n
+  | n < small = "small"
+  | n < big = "medium"
+  where
+    small = 10
+    big = 100
+classify n = "large"
+
+find k xs | Just v <- lookup k xs, v > 1 = v
+          | otherwise = 0
+
+max a b | a < b = b | otherwise = a
@@ -1 +1,4 @@
 double = \x -> 2 * x
+add = \x y -> x + y
+swap = \(a, b) -> (b, a)
+heads = \(x:xs) _ -> x
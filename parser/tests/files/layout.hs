@@ -0,0 +1,35 @@
+data Shape
+  = Circle Int
+  | Rect Int Int
+
+class Describe a where
+  describe :: a -> String
+  name :: a -> String
+  name x =
+    describe x
+
+instance Describe Shape where
+  describe (Circle r) = "circle"
+  describe s = "rect"
+
+area (Circle r) =
+  3 * r * r
+area (Rect w h) = w * h
+
+quad x =
+  let y =
+        2 * x
+  in 2 * y
+
+sign x =
+  if x < 0
+    then 0 - 1
+    else if x == 0
+      then 0
+      else 1
+
+(quad 3, sign (0 - 5), sign 0, sign 7,
+ area (Rect 2 3), name (Circle 1))
+
+let z = 3
+in z * z
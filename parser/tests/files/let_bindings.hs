@@ -0,0 +1,9 @@
+a = let { x = 1 ; y = x + 1 } in x + y
+
+b = let
+      go :: Int -> Int -> Int
+      go 0 acc = acc
+      go n acc = go (n - 1) (acc * n)
+    in go 5 1
+
+c = let {} in 3
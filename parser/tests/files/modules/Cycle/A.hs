@@ -0,0 +1,5 @@
+module Cycle.A where
+
+import Cycle.B
+
+a = b
@@ -0,0 +1,5 @@
+module Cycle.B where
+
+import Cycle.A
+
+b = 1
@@ -0,0 +1,6 @@
+module Data.Counter where
+
+next :: Int -> Int
+next n = n + 1
+
+reset n = 0
@@ -0,0 +1,12 @@
+module Geometry
+  ( Shape(..)
+  , area
+  ) where
+
+data Shape = Square Int | Rect Int Int
+
+helper x y = x * y
+
+area :: Shape -> Int
+area (Square n) = helper n n
+area (Rect w h) = helper w h
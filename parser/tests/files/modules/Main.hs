@@ -0,0 +1,7 @@
+import Geometry (Shape(..), area)
+import qualified Data.Counter as C
+import Data.Counter hiding (reset)
+
+helper = C.next 1
+
+total = area (Square 2) + helper + next 0
@@ -0,0 +1,3 @@
+module Other where
+
+next n = n - 1
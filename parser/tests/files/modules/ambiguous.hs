@@ -0,0 +1,4 @@
+import Other
+import Data.Counter
+
+x = next 1
@@ -0,0 +1,3 @@
+import Cycle.A
+
+x = a
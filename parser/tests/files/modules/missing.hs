@@ -0,0 +1,3 @@
+import Data.Missing
+
+x = 1
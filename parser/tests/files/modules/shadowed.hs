@@ -0,0 +1,8 @@
+import Other
+import Data.Counter
+
+-- An ambiguous name may be used where a local binding shadows it
+x = let next = 2 in next
+y = \next -> next
+z = case 1 of
+  next -> next
@@ -0,0 +1,15 @@
+infixr 9 .
+infixr 0 $
+infixl 9 !!
+infixl 6 <+>, <->
+infix 4 `elem`
+(.) f g x = f (g x)
+f $ x = f x
+(x:xs) !! 0 = x
+(x:xs) !! n = xs !! (n - 1)
+Nothing <+> y = y
+Just x <+> y = Just x
+a <-> b = a - b
+x `elem` [] = False
+x `elem` (y:ys) = (x == y) || (x `elem` ys)
+compose = (.)
@@ -9,3 +9,7 @@ open_step = [1,3..]
 
 closed_step :: [Int]
 closed_step = [1,3..30]
+
+upTo n = [1..n]
+downFrom n = [n, n - 1..0]
+letters = ['a'..'z']
@@ -0,0 +1,9 @@
+double = (* 2)
+halve = (`div` 2)
+decrement = (subtract 1)
+tenMinus = (10 -)
+plus = (+)
+cons = (:)
+max x y = if x < y then y else x
+larger = 3 `max` 4
+(1 `max`) 2
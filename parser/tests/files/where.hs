@@ -0,0 +1,13 @@
+area r = pi * r * r
+  where
+    pi = 3
+
+fact n = go n 1
+  where
+    go 0 acc = acc
+    go k acc = go (k - 1) (acc * k)
+    goal = 3
+
+norm (x, y) = sq x + sq y where sq z = z * z
+
+empty x = x where
@@ -1,7 +1,17 @@
 use ast::ast::Decl;
 use ast::ast::Expr;
+use ast::ast::Guard;
+use ast::ast::Import;
+use ast::ast::ImportList;
+use ast::ast::List;
+use ast::ast::Literal;
+use ast::ast::Op;
+use ast::ast::Pattern;
+use ast::ast::Rhs;
+use ast::ast::Type;
 use log::info;
-use parser::parse;
+use parser::{load, parse, parse_module};
+use std::path::Path;
 
 macro_rules! test_parse {
     ($($name:ident: $file:expr,)*) => {
@@ -42,6 +52,17 @@ test_parse! {
     ranges: "files/ranges.hs",
     letin: "files/let.hs",
     lambda: "files/lambda.hs",
+    data: "files/data.hs",
+    classes: "files/classes.hs",
+    layout: "files/layout.hs",
+    where_block: "files/where.hs",
+    guards: "files/guards.hs",
+    let_bindings: "files/let_bindings.hs",
+    sections: "files/sections.hs",
+    operators: "files/operators.hs",
+    comprehensions: "files/comprehensions.hs",
+    comments: "files/comments.hs",
+    case: "files/case.hs",
 }
 
 #[test]
@@ -58,3 +79,951 @@ fn application_multiple_args_test() {
         Decl::SExpr(Expr::Application(Box::new(Expr::Application(f, x)), y))
     );
 }
+
+#[test]
+fn data_decl_test() {
+    let _ = env_logger::try_init();
+    let src = "data Tree a = Leaf | Node (Tree a) a (Tree a)\n";
+    let program = parse(src).unwrap();
+    let tree = Type::Constructor(
+        "Tree".to_string(),
+        vec![Type::TypeVariable("a".to_string())],
+    );
+    assert_eq!(
+        program.first().unwrap().clone(),
+        Decl::DataDecl(
+            "Tree".to_string(),
+            vec!["a".to_string()],
+            vec![
+                ("Leaf".to_string(), vec![]),
+                (
+                    "Node".to_string(),
+                    vec![tree.clone(), Type::TypeVariable("a".to_string()), tree]
+                ),
+            ]
+        )
+    );
+}
+
+#[test]
+fn constructor_pattern_test() {
+    let _ = env_logger::try_init();
+    let src = "isLeaf Leaf = True\nisLeaf (Node l x r) = False\n";
+    let program = parse(src).unwrap();
+    let Decl::FunDecl(_, _, Expr::Case(_, cases)) = program.first().unwrap() else {
+        panic!("Expected a function declaration with a case expression");
+    };
+    let patterns: Vec<Pattern> = cases.iter().map(|(p, _)| p.clone()).collect();
+    assert_eq!(
+        patterns,
+        vec![
+            Pattern::Constructor("Leaf".to_string(), vec![]),
+            Pattern::Constructor(
+                "Node".to_string(),
+                vec![
+                    Pattern::Var("l".to_string()),
+                    Pattern::Var("x".to_string()),
+                    Pattern::Var("r".to_string())
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn class_decl_test() {
+    let _ = env_logger::try_init();
+    let src = "class Eq a => Ord a where { (<) :: a -> a -> Bool ; (>) x y = y < x }\n";
+    let program = parse(src).unwrap();
+    let a = Type::TypeVariable("a".to_string());
+    let compare = Type::Function(
+        Box::new(a.clone()),
+        Box::new(Type::Function(Box::new(a), Box::new(Type::Bool))),
+    );
+    let x = Box::new(Expr::Var(">:arg0".to_string()));
+    let y = Box::new(Expr::Var(">:arg1".to_string()));
+    let args = Pattern::FakeTuple(vec![
+        Pattern::Var(">:arg0".to_string()),
+        Pattern::Var(">:arg1".to_string()),
+    ]);
+    let body = Expr::Case(
+        Box::new(Expr::Tuple(vec![*x.clone(), *y.clone()])),
+        vec![(args, Rhs::Expr(Expr::BinOp(y, Op::Lt, x)))],
+    );
+    assert_eq!(
+        program.first().unwrap().clone(),
+        Decl::ClassDecl(
+            vec![("Eq".to_string(), "a".to_string())],
+            "Ord".to_string(),
+            "a".to_string(),
+            vec![
+                Decl::TypeSignature("<".to_string(), vec![], compare),
+                Decl::FunDecl(
+                    ">".to_string(),
+                    vec![">:arg1".to_string(), ">:arg0".to_string()],
+                    body
+                ),
+            ]
+        )
+    );
+}
+
+#[test]
+fn instance_decl_test() {
+    let _ = env_logger::try_init();
+    let src = "instance (Eq a, Show a) => Show [a] where { show xs = \"list\" }\n";
+    let program = parse(src).unwrap();
+    assert_eq!(
+        program.first().unwrap().clone(),
+        Decl::InstanceDecl(
+            vec![
+                ("Eq".to_string(), "a".to_string()),
+                ("Show".to_string(), "a".to_string())
+            ],
+            "Show".to_string(),
+            Type::List(Box::new(Type::TypeVariable("a".to_string()))),
+            vec![Decl::FunDecl(
+                "show".to_string(),
+                vec!["xs".to_string()],
+                Expr::Literal(Literal::String("list".to_string()))
+            )]
+        )
+    );
+}
+
+#[test]
+fn layout_test() {
+    let _ = env_logger::try_init();
+    let src = "quad x =\n  let y =\n        2 * x\n  in 2 * y\nclass C a where\n  f :: a -> a\n  g :: a\n";
+    let explicit =
+        "quad x = let { y = 2 * x } in 2 * y\nclass C a where { f :: a -> a ; g :: a }\n";
+    assert_eq!(parse(src).unwrap(), parse(explicit).unwrap());
+}
+
+#[test]
+fn layout_error_test() {
+    let _ = env_logger::try_init();
+    let misaligned = "g x =\n  let a = 1\n     b = 2\n  in a\n";
+    let err = parse(misaligned).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Layout error at line 3, column 6: indentation does not match any enclosing block"
+    );
+    let dedented = "  x = 1\ny = 2\n";
+    let err = parse(dedented).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Layout error at line 2, column 1: line is indented less than the top-level declarations"
+    );
+}
+
+#[test]
+fn error_position_test() {
+    let _ = env_logger::try_init();
+    // Errors are reported where they are in the source, not in the text the layout rule makes of it
+    let src = "x = 1\n\nf y = case y of\n  1 -> 2 3 ->\n  _ -> 0\n";
+    let err = parse(src).unwrap_err().to_string();
+    assert!(err.contains("--> 4:12\n"), "{}", err);
+    assert!(err.contains("4 |   1 -> 2 3 ->\n"), "{}", err);
+    let src = "a = 1\n\nb = let\n      c = 2\n    in c\n\nk = 3 +\n";
+    let err = parse(src).unwrap_err().to_string();
+    assert!(err.contains("--> 7:8\n"), "{}", err);
+    let err = parse("y = 1 +\n  2\n!x = y\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Strict binding at line 3, column 1 is not allowed at the top level"
+    );
+}
+
+#[test]
+fn where_test() {
+    let _ = env_logger::try_init();
+    let src = "f x = y\n  where\n    y = g x\n    g z = z\n";
+    let program = parse(src).unwrap();
+    let x = Box::new(Expr::Var("x".to_string()));
+    let g = Box::new(Expr::Var("g".to_string()));
+    let bindings = vec![
+        Decl::FunDecl("y".to_string(), vec![], Expr::Application(g, x)),
+        Decl::FunDecl(
+            "g".to_string(),
+            vec!["z".to_string()],
+            Expr::Var("z".to_string()),
+        ),
+    ];
+    let body = Expr::Let(bindings, Box::new(Expr::Var("y".to_string())));
+    assert_eq!(
+        program.first().unwrap().clone(),
+        Decl::FunDecl("f".to_string(), vec!["x".to_string()], body)
+    );
+}
+
+#[test]
+fn equations_of_prefixed_names_test() {
+    let _ = env_logger::try_init();
+    let src = "go 0 = 1\ngoal = 2\n";
+    let program = parse(src).unwrap();
+    assert_eq!(
+        program.get(1).unwrap().clone(),
+        Decl::FunDecl("goal".to_string(), vec![], Expr::Literal(Literal::Int(2)))
+    );
+}
+
+#[test]
+fn guards_test() {
+    let _ = env_logger::try_init();
+    let src = "f x | Just y <- x, y = 1\n    | otherwise = 0\n";
+    let program = parse(src).unwrap();
+    let x = Expr::Var("x".to_string());
+    let just_y = Pattern::Constructor("Just".to_string(), vec![Pattern::Var("y".to_string())]);
+    let rhs = Rhs::Guarded(vec![
+        (
+            vec![
+                Guard::Pattern(just_y, x),
+                Guard::Bool(Expr::Var("y".to_string())),
+            ],
+            Expr::Literal(Literal::Int(1)),
+        ),
+        (
+            vec![Guard::Bool(Expr::Var("otherwise".to_string()))],
+            Expr::Literal(Literal::Int(0)),
+        ),
+    ]);
+    let body = Expr::Case(
+        Box::new(Expr::Tuple(vec![])),
+        vec![(Pattern::Wildcard, rhs)],
+    );
+    assert_eq!(
+        program.first().unwrap().clone(),
+        Decl::FunDecl("f".to_string(), vec!["x".to_string()], body)
+    );
+}
+
+#[test]
+fn let_bindings_test() {
+    let _ = env_logger::try_init();
+    let src = "let { f :: Int -> Int ; f y = y ; x = 1 } in f x\n";
+    let program = parse(src).unwrap();
+    let f = Box::new(Expr::Var("f".to_string()));
+    let x = Box::new(Expr::Var("x".to_string()));
+    let decls = vec![
+        Decl::TypeSignature(
+            "f".to_string(),
+            vec![],
+            Type::Function(Box::new(Type::Int), Box::new(Type::Int)),
+        ),
+        Decl::FunDecl(
+            "f".to_string(),
+            vec!["y".to_string()],
+            Expr::Var("y".to_string()),
+        ),
+        Decl::FunDecl("x".to_string(), vec![], Expr::Literal(Literal::Int(1))),
+    ];
+    assert_eq!(
+        program.first().unwrap().clone(),
+        Decl::SExpr(Expr::Let(decls, Box::new(Expr::Application(f, x))))
+    );
+}
+
+#[test]
+fn let_duplicate_binding_test() {
+    let _ = env_logger::try_init();
+    let src = "let { x = 1 ; y = 2 ; x = 3 } in x\n";
+    let err = parse(src).unwrap_err();
+    assert_eq!(err.to_string(), "Found multiple Definitions for symbol 'x'");
+}
+
+#[test]
+fn varying_arity_test() {
+    let _ = env_logger::try_init();
+    let message = "Equations for ‘f’ have different numbers of arguments";
+    for src in [
+        "f 0 = 1\nf = 2\n",
+        "f x = 1\nf x y = 2\n",
+        "let { f x = 1 ; f = 2 } in f\n",
+    ] {
+        let err = parse(src).unwrap_err();
+        assert_eq!(err.to_string(), message);
+    }
+}
+
+#[test]
+fn lambda_patterns_test() {
+    let _ = env_logger::try_init();
+    let src = "\\x (a, b) -> x\n";
+    let program = parse(src).unwrap();
+    let arg0 = "\\:arg0".to_string();
+    let arg1 = "\\:arg1".to_string();
+    let args = Expr::Tuple(vec![Expr::Var(arg0.clone()), Expr::Var(arg1.clone())]);
+    let patterns = Pattern::FakeTuple(vec![
+        Pattern::Var("x".to_string()),
+        Pattern::Tuple(vec![
+            Pattern::Var("a".to_string()),
+            Pattern::Var("b".to_string()),
+        ]),
+    ]);
+    let body = Expr::Case(
+        Box::new(args),
+        vec![(patterns, Rhs::Expr(Expr::Var("x".to_string())))],
+    );
+    let lambda = Expr::Lambda(arg0, Box::new(Expr::Lambda(arg1, Box::new(body))));
+    assert_eq!(program.first().unwrap().clone(), Decl::SExpr(lambda));
+}
+
+#[test]
+fn sections_test() {
+    let _ = env_logger::try_init();
+    let src = "(+)\n(2 *)\n(`div` 2)\nx `max` y\n";
+    let program = parse(src).unwrap();
+    let two = Box::new(Expr::Literal(Literal::Int(2)));
+    let arg = "section:arg".to_string();
+    let div = Box::new(Expr::Var("div".to_string()));
+    let div_by_two = Expr::Application(
+        Box::new(Expr::Application(div, Box::new(Expr::Var(arg.clone())))),
+        two.clone(),
+    );
+    let max = Box::new(Expr::Var("max".to_string()));
+    let x = Box::new(Expr::Var("x".to_string()));
+    let y = Box::new(Expr::Var("y".to_string()));
+    assert_eq!(
+        program[..4],
+        [
+            Decl::SExpr(Expr::Operator(Op::Add)),
+            Decl::SExpr(Expr::Application(Box::new(Expr::Operator(Op::Mul)), two)),
+            Decl::SExpr(Expr::Lambda(arg, Box::new(div_by_two))),
+            Decl::SExpr(Expr::Application(Box::new(Expr::Application(max, x)), y)),
+        ]
+    );
+}
+
+#[test]
+fn fixity_test() {
+    let _ = env_logger::try_init();
+    let src = "infixr 5 <+>\ninfixl 6 |>\na <+> b |> c <+> d\n";
+    let program = parse(src).unwrap();
+    let var = |name: &str| Box::new(Expr::Var(name.to_string()));
+    let apply = |op: &str, l: Box<Expr>, r: Box<Expr>| {
+        Box::new(Expr::Application(
+            Box::new(Expr::Application(var(op), l)),
+            r,
+        ))
+    };
+    let expected = apply(
+        "<+>",
+        var("a"),
+        apply("<+>", apply("|>", var("b"), var("c")), var("d")),
+    );
+    assert_eq!(program.first().unwrap().clone(), Decl::SExpr(*expected));
+}
+
+#[test]
+fn operator_decl_test() {
+    let _ = env_logger::try_init();
+    let src = "x <+> y = y\n";
+    let program = parse(src).unwrap();
+    let args = Pattern::FakeTuple(vec![
+        Pattern::Var("<+>:arg0".to_string()),
+        Pattern::Var("<+>:arg1".to_string()),
+    ]);
+    let tuple = Expr::Tuple(vec![
+        Expr::Var("<+>:arg0".to_string()),
+        Expr::Var("<+>:arg1".to_string()),
+    ]);
+    let body = Expr::Case(
+        Box::new(tuple),
+        vec![(args, Rhs::Expr(Expr::Var("<+>:arg1".to_string())))],
+    );
+    assert_eq!(
+        program.first().unwrap().clone(),
+        Decl::FunDecl(
+            "<+>".to_string(),
+            vec!["<+>:arg1".to_string(), "<+>:arg0".to_string()],
+            body
+        )
+    );
+}
+
+#[test]
+fn fixity_conflict_test() {
+    let _ = env_logger::try_init();
+    let src = "infixl 6 <+>\ninfixr 6 <->\na <+> b <-> c\n";
+    let err = parse(src).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cannot mix '<+>' and '<->' in the same infix expression"
+    );
+}
+
+#[test]
+fn precedence_test() {
+    let _ = env_logger::try_init();
+    let src = "10 - 2 - 3\n1 + 2 * 3 == 7\nf x : map f xs\na || b && c\n";
+    let program = parse(src).unwrap();
+    let int = |n| Box::new(Expr::Literal(Literal::Int(n)));
+    let var = |name: &str| Box::new(Expr::Var(name.to_string()));
+    let binop = |l, op, r| Box::new(Expr::BinOp(l, op, r));
+    let app = |f, x| Box::new(Expr::Application(f, x));
+    let expected = [
+        binop(binop(int(10), Op::Sub, int(2)), Op::Sub, int(3)),
+        binop(
+            binop(int(1), Op::Add, binop(int(2), Op::Mul, int(3))),
+            Op::Eq,
+            int(7),
+        ),
+        binop(
+            app(var("f"), var("x")),
+            Op::Cons,
+            app(app(var("map"), var("f")), var("xs")),
+        ),
+        binop(var("a"), Op::Or, binop(var("b"), Op::And, var("c"))),
+    ];
+    assert_eq!(program[..4], expected.map(|e| Decl::SExpr(*e)));
+}
+
+#[test]
+fn non_associative_test() {
+    let _ = env_logger::try_init();
+    let err = parse("1 < 2 == True\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cannot mix '<' and '==' in the same infix expression"
+    );
+}
+
+#[test]
+fn comprehension_test() {
+    let _ = env_logger::try_init();
+    let src = "[y | let y = 1, y > 0]\n";
+    let program = parse(src).unwrap();
+    let y = Box::new(Expr::Var("y".to_string()));
+    let singleton = List::Some(y.clone(), Box::new(List::Empty));
+    let zero = Box::new(Expr::Literal(Literal::Int(0)));
+    let body = Expr::If(
+        Box::new(Expr::BinOp(y, Op::Gt, zero)),
+        Box::new(Expr::List(singleton)),
+        Box::new(Expr::List(List::Empty)),
+    );
+    let decls = vec![Decl::FunDecl(
+        "y".to_string(),
+        vec![],
+        Expr::Literal(Literal::Int(1)),
+    )];
+    assert_eq!(
+        program.first().unwrap().clone(),
+        Decl::SExpr(Expr::Let(decls, Box::new(body)))
+    );
+}
+
+#[test]
+fn range_test() {
+    let _ = env_logger::try_init();
+    let src = "[n, n - 1..0]\n";
+    let program = parse(src).unwrap();
+    let n = Box::new(Expr::Var("n".to_string()));
+    let then = Expr::BinOp(n.clone(), Op::Sub, Box::new(Expr::Literal(Literal::Int(1))));
+    let to = Expr::Literal(Literal::Int(0));
+    assert_eq!(
+        program.first().unwrap().clone(),
+        Decl::SExpr(Expr::Range(n, Some(Box::new(then)), Some(Box::new(to))))
+    );
+}
+
+#[test]
+fn negation_test() {
+    let _ = env_logger::try_init();
+    let src = "-2 ^ 2\n(-1)\n- x * y + 1\nx == -y\nf (-1) = 0\n";
+    let program = parse(src).unwrap();
+    let int = |n| Box::new(Expr::Literal(Literal::Int(n)));
+    let var = |name: &str| Box::new(Expr::Var(name.to_string()));
+    let negate = |e| Box::new(Expr::Application(var("negate"), e));
+    let app = |f, x| Box::new(Expr::Application(f, x));
+    let pow = |l, r| app(app(var("^"), l), r);
+    let binop = |l, op, r| Box::new(Expr::BinOp(l, op, r));
+    let expected = [
+        negate(pow(int(2), int(2))),
+        int(-1),
+        binop(negate(binop(var("x"), Op::Mul, var("y"))), Op::Add, int(1)),
+        binop(var("x"), Op::Eq, negate(var("y"))),
+    ];
+    assert_eq!(program[..4], expected.map(|e| Decl::SExpr(*e)));
+    let Decl::FunDecl(_, _, Expr::Case(_, cases)) = &program[4] else {
+        panic!("Expected a function matching on its argument");
+    };
+    assert_eq!(cases[0].0, Pattern::Literal(Literal::Int(-1)));
+}
+
+#[test]
+fn negation_precedence_test() {
+    let _ = env_logger::try_init();
+    let err = parse("1 + -2\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cannot mix '+' and '-' in the same infix expression"
+    );
+}
+
+#[test]
+fn integer_literal_test() {
+    let _ = env_logger::try_init();
+    let program =
+        parse("9223372036854775807\n9223372036854775808\n-9223372036854775809\n").unwrap();
+    let integer = |n: &str| Decl::SExpr(Expr::Literal(Literal::Integer(n.parse().unwrap())));
+    assert_eq!(
+        program[..3],
+        [
+            Decl::SExpr(Expr::Literal(Literal::Int(i64::MAX))),
+            integer("9223372036854775808"),
+            integer("-9223372036854775809"),
+        ]
+    );
+}
+
+#[test]
+fn double_literal_test() {
+    let _ = env_logger::try_init();
+    let program = parse("1.5\n2e3\n-2.5E-2\nf (-0.5) = 0\n[1..2]\n").unwrap();
+    let double = |x| Decl::SExpr(Expr::Literal(Literal::Double(x)));
+    assert_eq!(program[..3], [double(1.5), double(2000.0), double(-0.025)]);
+    let Decl::FunDecl(_, _, Expr::Case(_, cases)) = &program[3] else {
+        panic!("Expected a function matching on its argument");
+    };
+    assert_eq!(cases[0].0, Pattern::Literal(Literal::Double(-0.5)));
+    let int = |n| Box::new(Expr::Literal(Literal::Int(n)));
+    assert_eq!(
+        program[4],
+        Decl::SExpr(Expr::Range(int(1), None, Some(int(2))))
+    );
+}
+
+#[test]
+fn escape_test() {
+    let _ = env_logger::try_init();
+    let src = "'\\n'\n'\\''\n'\\x41'\n'\\o101'\n'\\65'\n'λ'\n\"\\SOH\\SO\\&H\\^A\\DEL\"\n\"tab\\t\\\"q\\\" ünï\\1234\\&5\"\n";
+    let program = parse(src).unwrap();
+    let char = |c| Decl::SExpr(Expr::Literal(Literal::Char(c)));
+    let string = |s: &str| Decl::SExpr(Expr::Literal(Literal::String(s.to_string())));
+    assert_eq!(
+        program[..8],
+        [
+            char('\n'),
+            char('\''),
+            char('A'),
+            char('A'),
+            char('A'),
+            char('λ'),
+            string("\u{1}\u{e}H\u{1}\u{7f}"),
+            string("tab\t\"q\" ünï\u{4d2}5"),
+        ]
+    );
+}
+
+#[test]
+fn literal_display_round_trip_test() {
+    let _ = env_logger::try_init();
+    let literals = [
+        Literal::Char('\''),
+        Literal::Char('"'),
+        Literal::Char('\u{7}'),
+        Literal::Char('λ'),
+        Literal::String("\"it's\"\n\\".to_string()),
+        Literal::String("\u{e}H \u{3bb}1 \u{0}\u{1f}".to_string()),
+    ];
+    for literal in literals {
+        let src = format!("{}\n", literal);
+        let program = parse(&src).unwrap();
+        assert_eq!(program[0], Decl::SExpr(Expr::Literal(literal)), "{}", src);
+    }
+    assert_eq!(Literal::Char('\u{e}').to_string(), "'\\SO'");
+    assert_eq!(
+        Literal::String("\u{e}H".to_string()).to_string(),
+        "\"\\SO\\&H\""
+    );
+    assert_eq!(
+        Literal::String("λ1".to_string()).to_string(),
+        "\"\\955\\&1\""
+    );
+}
+
+#[test]
+fn invalid_literal_test() {
+    let _ = env_logger::try_init();
+    for src in ["'\\q'\n", "'\\&'\n", "\"\\x110000\"\n", "\"\\^a\"\n"] {
+        let err = parse(src).unwrap_err();
+        assert_eq!(err.to_string(), format!("Invalid literal {}", src.trim()));
+    }
+}
+
+#[test]
+fn primed_identifier_test() {
+    let _ = env_logger::try_init();
+    let program = parse("go' x'' = x''\nif' = go' 1\n").unwrap();
+    let Decl::FunDecl(name, args, _) = &program[0] else {
+        panic!("Expected a function declaration");
+    };
+    assert_eq!((name.as_str(), args.len()), ("go'", 1));
+    let Decl::FunDecl(name, _, _) = &program[1] else {
+        panic!("Expected a function declaration");
+    };
+    assert_eq!(name, "if'");
+}
+
+#[test]
+fn radix_literal_test() {
+    let _ = env_logger::try_init();
+    let program = parse("0xFF\n0o17\n0B101\n0x1e3\n0x10000000000000000\n").unwrap();
+    let int = |n| Decl::SExpr(Expr::Literal(Literal::Int(n)));
+    let big = Decl::SExpr(Expr::Literal(Literal::Integer(
+        "18446744073709551616".parse().unwrap(),
+    )));
+    assert_eq!(program[..5], [int(255), int(15), int(5), int(0x1e3), big]);
+}
+
+#[test]
+fn comments_test() {
+    let _ = env_logger::try_init();
+    let commented = "-- leading\nf 0 = 1 -- base\n{- a {- nested -}\n block -}\nf n = n -- more\n  + 1 {- x -} * 2\n  where -- here\n    y = 1\n";
+    let plain = "f 0 = 1\nf n = n\n  + 1 * 2\n  where\n    y = 1\n";
+    assert_eq!(parse(commented).unwrap(), parse(plain).unwrap());
+}
+
+#[test]
+fn dashes_operator_test() {
+    let _ = env_logger::try_init();
+    let program = parse("x --> y\nx |-- y\n").unwrap();
+    let op = |name: &str| {
+        let partial = Expr::Application(
+            Box::new(Expr::Var(name.to_string())),
+            Box::new(Expr::Var("x".to_string())),
+        );
+        Decl::SExpr(Expr::Application(
+            Box::new(partial),
+            Box::new(Expr::Var("y".to_string())),
+        ))
+    };
+    assert_eq!(program[..2], [op("-->"), op("|--")]);
+}
+
+#[test]
+fn case_test() {
+    let _ = env_logger::try_init();
+    let one_line =
+        parse("case n of { 0 -> x; -1 | b -> y; m -> z }\ncase n of 0 -> x; -1 | b -> y; m -> z\n")
+            .unwrap();
+    let indented = parse("case n of\n  0 -> x\n  -1\n    | b -> y\n  m -> z\n").unwrap();
+    let var = |name: &str| Expr::Var(name.to_string());
+    let expected = Decl::SExpr(Expr::Case(
+        Box::new(var("n")),
+        vec![
+            (Pattern::Literal(Literal::Int(0)), Rhs::Expr(var("x"))),
+            (
+                Pattern::Literal(Literal::Int(-1)),
+                Rhs::Guarded(vec![(vec![Guard::Bool(var("b"))], var("y"))]),
+            ),
+            (Pattern::Var("m".to_string()), Rhs::Expr(var("z"))),
+        ],
+    ));
+    assert_eq!(one_line[..2], [expected.clone(), expected.clone()]);
+    assert_eq!(indented[0], expected);
+}
+
+#[test]
+fn do_test() {
+    let _ = env_logger::try_init();
+    let one_line = parse("do { x <- a; let { y = x }; b y; c }\n").unwrap();
+    let indented = parse("do\n  x <- a\n  let y = x\n  b y\n  c\n").unwrap();
+    let var = |name: &str| Expr::Var(name.to_string());
+    let app = |f: Expr, x: Expr| Expr::Application(Box::new(f), Box::new(x));
+    let expected = Decl::SExpr(app(
+        app(var(">>="), var("a")),
+        Expr::Lambda(
+            "x".to_string(),
+            Box::new(Expr::Let(
+                vec![Decl::FunDecl("y".to_string(), vec![], var("x"))],
+                Box::new(app(app(var(">>"), app(var("b"), var("y"))), var("c"))),
+            )),
+        ),
+    ));
+    assert_eq!(one_line[0], expected);
+    assert_eq!(indented[0], expected);
+    let unit = parse("main :: IO ()\nmain = return ()\n").unwrap();
+    assert_eq!(
+        unit[0],
+        Decl::TypeSignature(
+            "main".to_string(),
+            vec![],
+            Type::IO(Box::new(Type::Tuple(vec![])))
+        )
+    );
+    let err = parse("do { x <- a }\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The last statement of a do block must be an expression"
+    );
+}
+
+#[test]
+fn annotation_test() {
+    let _ = env_logger::try_init();
+    let var = |name: &str| Expr::Var(name.to_string());
+    let int = || Type::Int;
+    let annotated = |e: Expr, t: Type| Expr::Annotated(Box::new(e), Box::new(t));
+    // An annotation scopes over the whole infix expression to its left
+    let sum = parse("1 + 2 :: Int\n").unwrap();
+    let Decl::SExpr(e) = &parse("1 + 2\n").unwrap()[0] else {
+        panic!("Expected an expression");
+    };
+    assert_eq!(sum[0], Decl::SExpr(annotated(e.clone(), int())));
+    let lambda = parse("\\(x :: Int) -> x\n").unwrap();
+    let expected = Expr::Lambda(
+        "x".to_string(),
+        Box::new(Expr::Case(
+            Box::new(annotated(var("x"), int())),
+            vec![(Pattern::Var("x".to_string()), Rhs::Expr(var("x")))],
+        )),
+    );
+    assert_eq!(lambda[0], Decl::SExpr(expected));
+    let binding = parse("let\n  (xs :: [a]) = []\nin xs\n").unwrap();
+    let list = Type::List(Box::new(Type::TypeVariable("a".to_string())));
+    let expected = Expr::Let(
+        vec![Decl::FunDecl(
+            "xs".to_string(),
+            vec![],
+            annotated(Expr::List(List::Empty), list),
+        )],
+        Box::new(var("xs")),
+    );
+    assert_eq!(binding[0], Decl::SExpr(expected));
+    let binding = parse("let (y :: Int) = 3 in y\n").unwrap();
+    let expected = Expr::Let(
+        vec![Decl::FunDecl(
+            "y".to_string(),
+            vec![],
+            annotated(Expr::Literal(Literal::Int(3)), int()),
+        )],
+        Box::new(var("y")),
+    );
+    assert_eq!(binding[0], Decl::SExpr(expected));
+    // A binding is annotated by a parenthesised pattern, as in Haskell
+    assert!(parse("let y :: Int = 3 in y\n").is_err());
+}
+
+#[test]
+fn nested_pattern_test() {
+    let _ = env_logger::try_init();
+    let var = |name: &str| Pattern::Var(name.to_string());
+    let cons = |p1, p2| Pattern::List(Box::new(p1), Box::new(p2));
+    let Decl::FunDecl(_, _, e) = &parse("f ((a, b):rest) = a\n").unwrap()[0] else {
+        panic!("Expected a function declaration");
+    };
+    let Expr::Case(_, alternatives) = e else {
+        panic!("Expected a case expression");
+    };
+    let tuple = Pattern::Tuple(vec![var("a"), var("b")]);
+    assert_eq!(alternatives[0].0, cons(tuple, var("rest")));
+    let src = "case l of\n  [x, y] -> x\n  xs@(x:_) -> x\n";
+    let Decl::SExpr(Expr::Case(_, alternatives)) = &parse(src).unwrap()[0] else {
+        panic!("Expected a case expression");
+    };
+    let elems = cons(var("x"), cons(var("y"), Pattern::EmptyList));
+    assert_eq!(alternatives[0].0, elems);
+    let as_pattern = Pattern::As(
+        "xs".to_string(),
+        Box::new(cons(var("x"), Pattern::Wildcard)),
+    );
+    assert_eq!(alternatives[1].0, as_pattern);
+}
+
+#[test]
+fn pattern_binding_test() {
+    let _ = env_logger::try_init();
+    let var = |name: &str| Expr::Var(name.to_string());
+    let pattern = Pattern::Tuple(vec![
+        Pattern::Var("q".to_string()),
+        Pattern::Var("r".to_string()),
+    ]);
+    let decls = parse("(q, r) = p\n").unwrap();
+    let hidden = "pat:q:r".to_string();
+    let select = |name: &str| {
+        let alternative = (pattern.clone(), Rhs::Expr(var(name)));
+        let body = Expr::Case(Box::new(Expr::Var(hidden.clone())), vec![alternative]);
+        Decl::FunDecl(name.to_string(), vec![], body)
+    };
+    let expected = vec![
+        Decl::FunDecl(hidden.clone(), vec![], var("p")),
+        select("q"),
+        select("r"),
+    ];
+    assert_eq!(decls[..3], expected);
+    let Decl::SExpr(Expr::Let(decls, _)) = &parse("let (q, r) = p in q\n").unwrap()[0] else {
+        panic!("Expected a let expression");
+    };
+    assert_eq!(decls, &expected);
+    // A pattern without variables still binds its value, under a name made of its position
+    let decls = parse("x = 1\n(_, _) = p\n").unwrap();
+    let expected = Decl::FunDecl("pat:2:1".to_string(), vec![], var("p"));
+    assert_eq!(decls[1], expected);
+}
+
+#[test]
+fn strictness_pattern_test() {
+    let _ = env_logger::try_init();
+    let var = |name: &str| Pattern::Var(name.to_string());
+    let Decl::FunDecl(_, _, e) = &parse("f !x ~(a, b) = x\n").unwrap()[0] else {
+        panic!("Expected a function declaration");
+    };
+    let Expr::Case(_, alternatives) = e else {
+        panic!("Expected a case expression");
+    };
+    let lazy = Pattern::Lazy(Box::new(Pattern::Tuple(vec![var("a"), var("b")])));
+    let expected = Pattern::FakeTuple(vec![Pattern::Bang(Box::new(var("x"))), lazy]);
+    assert_eq!(alternatives[0].0, expected);
+    // With a space after it, `!` is an operator being defined
+    let decl = parse("xs ! n = n\n").unwrap();
+    assert!(matches!(&decl[0], Decl::FunDecl(name, _, _) if name == "!"));
+    let Decl::SExpr(Expr::Let(_, body)) = &parse("let !x = y in x\n").unwrap()[0] else {
+        panic!("Expected a let expression");
+    };
+    let force = (
+        Pattern::Bang(Box::new(Pattern::Wildcard)),
+        Rhs::Expr(Expr::Var("x".to_string())),
+    );
+    let expected = Expr::Case(Box::new(Expr::Var("pat:x".to_string())), vec![force]);
+    assert_eq!(**body, expected);
+    // The bindings of a where block are forced the same way
+    let Decl::FunDecl(_, _, Expr::Let(_, body)) = &parse("f = x where !x = y\n").unwrap()[0] else {
+        panic!("Expected a function declaration with a where block");
+    };
+    assert_eq!(**body, expected);
+    let err = parse("y = 1\n!x = y\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Strict binding at line 2, column 1 is not allowed at the top level"
+    );
+}
+
+#[test]
+fn module_header_test() {
+    let _ = env_logger::try_init();
+    let src = "module Data.Shapes (area, (<+>)) where\nimport Prelude.Extra\nimport qualified Data.Counter as C hiding (reset)\nimport Geometry (norm)\n\narea = C.next Geometry.norm\n";
+    let module = parse_module(src).unwrap();
+    assert_eq!(module.name, "Data.Shapes");
+    let exports = vec!["area".to_string(), "<+>".to_string()];
+    assert_eq!(module.exports, Some(exports));
+    let import = |module: &str, qualified, alias: &str, names| Import {
+        module: module.to_string(),
+        qualified,
+        alias: alias.to_string(),
+        names,
+    };
+    let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+    let imports = vec![
+        import("Prelude.Extra", false, "Prelude.Extra", ImportList::All),
+        import(
+            "Data.Counter",
+            true,
+            "C",
+            ImportList::Hiding(names(&["reset"])),
+        ),
+        import(
+            "Geometry",
+            false,
+            "Geometry",
+            ImportList::Only(names(&["norm"])),
+        ),
+    ];
+    assert_eq!(module.imports, imports);
+    let body = Expr::Application(
+        Box::new(Expr::Var("C.next".to_string())),
+        Box::new(Expr::Var("Geometry.norm".to_string())),
+    );
+    assert_eq!(
+        module.program[0],
+        Decl::FunDecl("area".to_string(), vec![], body)
+    );
+    let main = parse_module("x = 1\n").unwrap();
+    assert_eq!((main.name.as_str(), main.exports), ("Main", None));
+    // Types are always in scope, so listing them is left out
+    let module = parse_module("module A (Shape(..), x) where\nx = 1\n").unwrap();
+    assert_eq!(module.exports, Some(vec!["x".to_string()]));
+    let module = parse_module("import A (x, Point, Shape(Sq, Rect))\nx = 1\n").unwrap();
+    assert_eq!(module.imports[0].names, ImportList::Only(names(&["x"])));
+}
+
+#[test]
+fn load_modules_test() {
+    let _ = env_logger::try_init();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/files/modules");
+    let modules = load(&dir.join("Main.hs"), &[]).unwrap();
+    let names = modules.iter().map(|m| m.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["Geometry", "Data.Counter", "Main"]);
+    let value_names = |program: &[Decl]| {
+        program
+            .iter()
+            .filter_map(|decl| match decl {
+                Decl::FunDecl(name, _, _) => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        value_names(&modules[0].program),
+        ["Geometry.helper", "Geometry.area"]
+    );
+    assert_eq!(modules[0].exports, Some(vec!["Geometry.area".to_string()]));
+    assert_eq!(value_names(&modules[2].program), ["helper", "total"]);
+    let var = |name: &str| Box::new(Expr::Var(name.to_string()));
+    let app = |f, x| Box::new(Expr::Application(f, x));
+    let total = Expr::BinOp(
+        Box::new(Expr::BinOp(
+            app(
+                var("Geometry.area"),
+                app(
+                    Box::new(Expr::Constructor("Square".to_string())),
+                    Box::new(Expr::Literal(Literal::Int(2))),
+                ),
+            ),
+            Op::Add,
+            var("helper"),
+        )),
+        Op::Add,
+        app(
+            var("Data.Counter.next"),
+            Box::new(Expr::Literal(Literal::Int(0))),
+        ),
+    );
+    assert_eq!(
+        modules[2].program[1],
+        Decl::FunDecl("total".to_string(), vec![], total)
+    );
+    let helper = app(
+        var("Data.Counter.next"),
+        Box::new(Expr::Literal(Literal::Int(1))),
+    );
+    assert_eq!(
+        modules[2].program[0],
+        Decl::FunDecl("helper".to_string(), vec![], *helper)
+    );
+}
+
+#[test]
+fn load_errors_test() {
+    let _ = env_logger::try_init();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/files/modules");
+    let err = load(&dir.join("cycle.hs"), &[]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Modules import each other in a cycle: Cycle.A -> Cycle.B -> Cycle.A"
+    );
+    let err = load(&dir.join("missing.hs"), &[]).unwrap_err();
+    assert_eq!(err.to_string(), "Could not find module Data.Missing");
+    let err = load(&dir.join("ambiguous.hs"), &[]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Ambiguous name next, which could refer to Data.Counter.next or Other.next"
+    );
+    assert!(load(&dir.join("shadowed.hs"), &[]).is_ok());
+    let err = load(&dir.join("Cycle/A.hs"), &[dir]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Modules import each other in a cycle: Cycle.A -> Cycle.B -> Cycle.A"
+    );
+}
@@ -1,36 +1,59 @@
-use std::{env, process::exit};
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+    process::exit,
+};
 
-use eval::eval;
-use parser::parse;
-use typechecker::typecheck;
+use ast::ast::Decl;
+use eval::{eval, run_main};
+use parser::load;
+use typechecker::typecheck_modules;
 
 fn main() {
     env_logger::init();
-    match env::args().collect::<Vec<_>>().get(1) {
+    let mut search_path = vec![];
+    let mut file = None;
+    for arg in env::args().skip(1) {
+        match arg.strip_prefix("-i") {
+            // Directories to look up imported modules in, given as `-idir1:dir2` like for GHC
+            Some(dirs) => search_path.extend(
+                dirs.split(':')
+                    .filter(|dir| !dir.is_empty())
+                    .map(PathBuf::from),
+            ),
+            None => file = Some(arg),
+        }
+    }
+    match file {
         Some(path) => {
-            println!("Trying to open {}.", path);
-            match std::fs::read_to_string(path) {
-                Ok(source) => {
-                    let p = parse(&source).unwrap();
-                    match typecheck(&p) {
-                        Ok(_) => {},
-                        Err(err) => {
-                            eprintln!("Typing Error: {}", err);
-                            exit(-1)
-
-                        },
-                    }
-                    match eval(p) {
-                        Ok(()) => {}
-                        Err(err) => {
-                            eprintln!("Runtime Error: {}", err);
-                            exit(-1)
-                        }
-                    }
+            // Only the program itself writes to stdout when it runs `main`
+            eprintln!("Trying to open {}.", path);
+            let modules = match load(Path::new(&path), &search_path) {
+                Ok(modules) => modules,
+                Err(err) => {
+                    eprintln!("Parsing Error: {}", err);
+                    exit(-1)
+                }
+            };
+            let p = match typecheck_modules(&modules) {
+                Ok(p) => p,
+                Err(err) => {
+                    eprintln!("Typing Error: {}", err);
+                    exit(-1)
                 }
+            };
+            let has_main = p
+                .iter()
+                .any(|decl| matches!(decl, Decl::FunDecl(name, _, _) if name == "main"));
+            let result = match has_main {
+                true => run_main(p, &mut io::stdin().lock(), &mut io::stdout().lock()),
+                false => eval(p),
+            };
+            match result {
+                Ok(()) => {}
                 Err(err) => {
-                    eprintln!("Ran into error while trying to open the file.");
-                    eprintln!("{}", err);
+                    eprintln!("Runtime Error: {}", err);
+                    exit(-1)
                 }
             }
         }
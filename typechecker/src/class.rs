@@ -0,0 +1,97 @@
+use std::collections::{HashMap, HashSet};
+
+use ast::ast::{Constraint, Expr, Pattern, Rhs, Type};
+
+#[derive(Clone, Debug)]
+pub struct Class {
+    pub superclasses: Vec<String>,
+    pub var: String,
+    pub methods: Vec<(String, Type)>,
+    pub defaults: HashSet<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Instance {
+    /// The type variables the instance head is applied to, e.g. `a` in `instance Eq [a]`
+    pub params: Vec<String>,
+    pub context: Vec<Constraint>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ClassEnvironment {
+    pub classes: HashMap<String, Class>,
+    pub instances: HashMap<(String, String), Instance>,
+}
+
+impl ClassEnvironment {
+    pub fn instance(&self, class: &str, head: &str) -> Option<&Instance> {
+        self.instances.get(&(class.to_string(), head.to_string()))
+    }
+
+    /// Builds an expression selecting the dictionary of `target` out of the dictionary `dict` of
+    /// `class`, following superclass links.
+    pub fn by_superclass(&self, class: &str, dict: Expr, target: &str) -> Option<Expr> {
+        if class == target {
+            return Some(dict);
+        }
+        let superclasses = &self.classes.get(class)?.superclasses;
+        superclasses.iter().find_map(|superclass| {
+            let selector = Expr::Var(superclass_selector(class, superclass));
+            let super_dict = Expr::Application(Box::new(selector), Box::new(dict.clone()));
+            self.by_superclass(superclass, super_dict, target)
+        })
+    }
+}
+
+/// Splits a type into the name of its outermost type constructor and its arguments, which is what
+/// instances are indexed by.
+pub fn type_head(t: &Type) -> Option<(String, Vec<Type>)> {
+    match t {
+        Type::TypeVariable(_) => None,
+        Type::Function(t1, t2) => Some(("->".to_string(), vec![*t1.clone(), *t2.clone()])),
+        Type::Tuple(ts) => Some((
+            format!("({})", ",".repeat(ts.len().saturating_sub(1))),
+            ts.clone(),
+        )),
+        Type::List(t) => Some(("[]".to_string(), vec![*t.clone()])),
+        Type::IO(t) => Some(("IO".to_string(), vec![*t.clone()])),
+        Type::Constructor(name, ts) => Some((name.clone(), ts.clone())),
+        Type::Int => Some(("Int".to_string(), vec![])),
+        Type::Integer => Some(("Integer".to_string(), vec![])),
+        Type::Double => Some(("Double".to_string(), vec![])),
+        Type::Bool => Some(("Bool".to_string(), vec![])),
+        Type::Char => Some(("Char".to_string(), vec![])),
+        Type::String => Some(("String".to_string(), vec![])),
+    }
+}
+
+pub fn dictionary_name(class: &str, head: &str) -> String {
+    format!("dict:{}:{}", class, head)
+}
+
+pub fn superclass_selector(class: &str, superclass: &str) -> String {
+    format!("super:{}:{}", class, superclass)
+}
+
+pub fn default_method(class: &str, method: &str) -> String {
+    format!("default:{}:{}", class, method)
+}
+
+/// A function projecting the `index`-th field out of a dictionary with `size` fields
+pub fn selector(size: usize, index: usize) -> (Vec<String>, Expr) {
+    let dict = "dict".to_string();
+    let field = "field".to_string();
+    let pattern = Pattern::Tuple(
+        (0..size)
+            .map(|i| match i == index {
+                true => Pattern::Var(field.clone()),
+                false => Pattern::Wildcard,
+            })
+            .collect(),
+    );
+    let body = Expr::Case(
+        Box::new(Expr::Var(dict.clone())),
+        vec![(pattern, Rhs::Expr(Expr::Var(field)))],
+    );
+    (vec![dict], body)
+}
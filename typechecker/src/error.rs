@@ -1,4 +1,6 @@
 use ast::ast::Type;
+
+use crate::kind::Kind;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -11,4 +13,36 @@ pub enum TypingError {
     CannotUnify(Type, Type),
     #[error("Duplicate Type Variable")]
     DuplicateTypeVariable(String),
+    #[error("Unknown type {0}")]
+    UnknownType(String),
+    #[error("Type variable {0} is not bound by the data declaration")]
+    UnboundTypeVariable(String),
+    #[error("Expected kind {1} for type {0}, but found kind {2}")]
+    KindMismatch(Type, Kind, Kind),
+    #[error("Constructor {0} expects {1} arguments, but the pattern has {2}")]
+    ConstructorArity(String, usize, usize),
+    #[error("Unknown class {0}")]
+    UnknownClass(String),
+    #[error("No instance for {0} {1}")]
+    NoInstance(String, Type),
+    #[error("Could not deduce {0} {1} from the context")]
+    CannotDeduce(String, Type),
+    #[error("Ambiguous type variable {1} in the constraint {0} {1}")]
+    AmbiguousType(String, Type),
+    #[error("{0} is defined by both module {1} and module {2}")]
+    DefinedByModules(String, String, String),
+    #[error("Multiple declarations of {0}")]
+    MultipleDeclarations(String),
+    #[error("Duplicate instance {0} {1}")]
+    DuplicateInstance(String, Type),
+    #[error("Instance head {0} must be a type constructor applied to distinct type variables")]
+    InvalidInstanceHead(Type),
+    #[error("{1} is not a method of class {0}")]
+    UnknownMethod(String, String),
+    #[error("No implementation of method {1} in an instance of class {0}")]
+    MissingMethod(String, String),
+    #[error("The type signature for {0} lacks an accompanying binding")]
+    MissingBinding(String),
+    #[error("The type variable {0} of an annotation would escape its scope")]
+    EscapedTypeVariable(String),
 }
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use ast::ast::Type;
+
+use crate::error::TypingError;
+
+pub type KindEnvironment = HashMap<String, Kind>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kind {
+    Star,
+    Arrow(Box<Kind>, Box<Kind>),
+}
+
+impl Kind {
+    /// The kind of a type constructor taking `arity` arguments of kind `*`
+    ///
+    /// This is the kind given to every data type: the grammar only applies
+    /// type constructors, never type variables, so a parameter can't be used
+    /// at a higher kind and `data Wrap f a = Wrap (f a)` is not accepted.
+    /// Checking kinds is then a matter of checking arities, with no inference.
+    pub fn of_arity(arity: usize) -> Kind {
+        (0..arity).fold(Kind::Star, |acc, _| {
+            Kind::Arrow(Box::new(Kind::Star), Box::new(acc))
+        })
+    }
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Star => write!(f, "*"),
+            Kind::Arrow(k1, k2) if matches!(**k1, Kind::Arrow(_, _)) => {
+                write!(f, "({}) -> {}", k1, k2)
+            }
+            Kind::Arrow(k1, k2) => write!(f, "{} -> {}", k1, k2),
+        }
+    }
+}
+
+/// Replaces every type name bound by a data declaration with the corresponding type constructor
+/// and every type alias with the type it stands for, and checks that the result is a proper type
+/// of kind `*`.
+pub fn resolve_type(
+    kind_env: &KindEnvironment,
+    aliases: &HashMap<String, Type>,
+    t: &Type,
+) -> Result<Type, TypingError> {
+    let t = resolve_names(kind_env, aliases, t)?;
+    expect_kind(kind_env, &t, &Kind::Star)?;
+    Ok(t)
+}
+
+fn resolve_names(
+    kind_env: &KindEnvironment,
+    aliases: &HashMap<String, Type>,
+    t: &Type,
+) -> Result<Type, TypingError> {
+    let resolve = |t: &Type| resolve_names(kind_env, aliases, t);
+    Ok(match t {
+        Type::TypeVariable(name) if kind_env.contains_key(name) => {
+            Type::Constructor(name.clone(), vec![])
+        }
+        Type::TypeVariable(name) if aliases.contains_key(name) => aliases[name].clone(),
+        // Type variables are lower case, so any other name has to be declared
+        Type::TypeVariable(name) if name.starts_with(char::is_uppercase) => {
+            return Err(TypingError::UnknownType(name.clone()))
+        }
+        Type::Function(t1, t2) => Type::Function(Box::new(resolve(t1)?), Box::new(resolve(t2)?)),
+        Type::Tuple(ts) => Type::Tuple(ts.iter().map(resolve).collect::<Result<_, _>>()?),
+        Type::List(t) => Type::List(Box::new(resolve(t)?)),
+        Type::IO(t) => Type::IO(Box::new(resolve(t)?)),
+        Type::String => Type::List(Box::new(Type::Char)),
+        Type::Constructor(name, args) => Type::Constructor(
+            name.clone(),
+            args.iter().map(resolve).collect::<Result<_, _>>()?,
+        ),
+        t => t.clone(),
+    })
+}
+
+fn expect_kind(kind_env: &KindEnvironment, t: &Type, expected: &Kind) -> Result<(), TypingError> {
+    let found = kind_of(kind_env, t)?;
+    if found != *expected {
+        return Err(TypingError::KindMismatch(
+            t.clone(),
+            expected.clone(),
+            found,
+        ));
+    }
+    Ok(())
+}
+
+fn kind_of(kind_env: &KindEnvironment, t: &Type) -> Result<Kind, TypingError> {
+    match t {
+        Type::Constructor(name, args) => {
+            let mut kind = kind_env
+                .get(name)
+                .cloned()
+                .ok_or(TypingError::UnknownType(name.clone()))?;
+            for arg in args {
+                match kind {
+                    Kind::Arrow(arg_kind, result_kind) => {
+                        expect_kind(kind_env, arg, &arg_kind)?;
+                        kind = *result_kind;
+                    }
+                    Kind::Star => {
+                        let expected = Kind::of_arity(args.len());
+                        let found = kind_env[name].clone();
+                        return Err(TypingError::KindMismatch(
+                            Type::Constructor(name.clone(), vec![]),
+                            expected,
+                            found,
+                        ));
+                    }
+                }
+            }
+            Ok(kind)
+        }
+        Type::Function(t1, t2) => {
+            expect_kind(kind_env, t1, &Kind::Star)?;
+            expect_kind(kind_env, t2, &Kind::Star)?;
+            Ok(Kind::Star)
+        }
+        Type::Tuple(ts) => {
+            for t in ts {
+                expect_kind(kind_env, t, &Kind::Star)?;
+            }
+            Ok(Kind::Star)
+        }
+        Type::List(t) | Type::IO(t) => {
+            expect_kind(kind_env, t, &Kind::Star)?;
+            Ok(Kind::Star)
+        }
+        Type::TypeVariable(_)
+        | Type::Int
+        | Type::Integer
+        | Type::Double
+        | Type::Bool
+        | Type::Char
+        | Type::String => Ok(Kind::Star),
+    }
+}
@@ -1,13 +1,22 @@
-use ast::ast::Program;
+use ast::ast::{Module, Program};
 use error::TypingError;
 use typecheck::typecheck_program;
 
+mod class;
 mod error;
+mod kind;
 mod subst;
 mod typecheck;
 mod util;
 
-pub fn typecheck(p: &Program) -> Result<(), TypingError> {
-    let _ = typecheck_program(p)?;
-    Ok(())
+/// Typechecks a program and returns it elaborated for evaluation: the prelude is prepended and
+/// class dictionaries are passed explicitly.
+pub fn typecheck(p: &Program) -> Result<Program, TypingError> {
+    typecheck_program(p)
+}
+
+/// Typechecks the modules of a program, as loaded by `parser::load`, and returns them elaborated
+/// into a single program after the prelude
+pub fn typecheck_modules(modules: &[Module]) -> Result<Program, TypingError> {
+    typecheck::typecheck_modules(modules)
 }
@@ -0,0 +1,313 @@
+not True = False
+not False = True
+
+otherwise = True
+
+class Eq a where
+  (==) :: a -> a -> Bool
+  (/=) :: a -> a -> Bool
+  (==) x y = not (x /= y)
+  (/=) x y = not (x == y)
+
+class Eq a => Ord a where
+  (<) :: a -> a -> Bool
+  (<=) :: a -> a -> Bool
+  (>) :: a -> a -> Bool
+  (>=) :: a -> a -> Bool
+  (<) x y = not (y <= x)
+  (<=) x y = not (y < x)
+  (>) x y = y < x
+  (>=) x y = y <= x
+
+class Show a where
+  show :: a -> String
+  showList :: [a] -> String
+  showList [] = "[]"
+  showList (x:xs) = "[" ++ show x ++ showItems xs where { showItems [] = "]"; showItems (y:ys) = "," ++ show y ++ showItems ys }
+
+class Enum a
+
+class Num a where
+  (+) :: a -> a -> a
+  (-) :: a -> a -> a
+  (*) :: a -> a -> a
+  negate :: a -> a
+  abs :: a -> a
+  signum :: a -> a
+  fromInteger :: Integer -> a
+
+class (Ord a, Num a) => Integral a where
+  div :: a -> a -> a
+  mod :: a -> a -> a
+  quot :: a -> a -> a
+  rem :: a -> a -> a
+  toInteger :: a -> Integer
+
+class Num a => Fractional a where
+  (/) :: a -> a -> a
+  recip :: a -> a
+  fromDouble :: Double -> a
+  recip x = 1 / x
+
+class Fractional a => Floating a where
+  pi :: a
+  exp :: a -> a
+  log :: a -> a
+  sqrt :: a -> a
+  sin :: a -> a
+  cos :: a -> a
+  tan :: a -> a
+  asin :: a -> a
+  acos :: a -> a
+  atan :: a -> a
+  (**) :: a -> a -> a
+  logBase :: a -> a -> a
+  (**) x y = exp (log x * y)
+  logBase b x = log x / log b
+
+primIntEq :: Int -> Int -> Bool
+primIntLt :: Int -> Int -> Bool
+primIntShow :: Int -> String
+primIntAdd :: Int -> Int -> Int
+primIntSub :: Int -> Int -> Int
+primIntMul :: Int -> Int -> Int
+primIntNegate :: Int -> Int
+primIntAbs :: Int -> Int
+primIntSignum :: Int -> Int
+primIntDiv :: Int -> Int -> Int
+primIntMod :: Int -> Int -> Int
+primIntQuot :: Int -> Int -> Int
+primIntRem :: Int -> Int -> Int
+primIntFromInteger :: Integer -> Int
+primIntToInteger :: Int -> Integer
+primIntegerEq :: Integer -> Integer -> Bool
+primIntegerLt :: Integer -> Integer -> Bool
+primIntegerShow :: Integer -> String
+primIntegerAdd :: Integer -> Integer -> Integer
+primIntegerSub :: Integer -> Integer -> Integer
+primIntegerMul :: Integer -> Integer -> Integer
+primIntegerNegate :: Integer -> Integer
+primIntegerAbs :: Integer -> Integer
+primIntegerSignum :: Integer -> Integer
+primIntegerDiv :: Integer -> Integer -> Integer
+primIntegerMod :: Integer -> Integer -> Integer
+primIntegerQuot :: Integer -> Integer -> Integer
+primIntegerRem :: Integer -> Integer -> Integer
+primIntegerExponent :: Integer -> Integer
+primDoubleEq :: Double -> Double -> Bool
+primDoubleLt :: Double -> Double -> Bool
+primDoubleLe :: Double -> Double -> Bool
+primDoubleGt :: Double -> Double -> Bool
+primDoubleGe :: Double -> Double -> Bool
+primDoubleShow :: Double -> String
+primDoubleAdd :: Double -> Double -> Double
+primDoubleSub :: Double -> Double -> Double
+primDoubleMul :: Double -> Double -> Double
+primDoubleDiv :: Double -> Double -> Double
+primDoubleNegate :: Double -> Double
+primDoubleAbs :: Double -> Double
+primDoubleSignum :: Double -> Double
+primDoubleFromInteger :: Integer -> Double
+primDoubleTruncate :: Double -> Integer
+primDoubleRound :: Double -> Integer
+primDoubleFloor :: Double -> Integer
+primDoubleCeiling :: Double -> Integer
+primDoubleExp :: Double -> Double
+primDoubleLog :: Double -> Double
+primDoubleSqrt :: Double -> Double
+primDoubleSin :: Double -> Double
+primDoubleCos :: Double -> Double
+primDoubleTan :: Double -> Double
+primDoubleAsin :: Double -> Double
+primDoubleAcos :: Double -> Double
+primDoubleAtan :: Double -> Double
+primDoublePow :: Double -> Double -> Double
+primBoolEq :: Bool -> Bool -> Bool
+primBoolLt :: Bool -> Bool -> Bool
+primBoolShow :: Bool -> String
+primCharEq :: Char -> Char -> Bool
+primCharLt :: Char -> Char -> Bool
+primCharShow :: Char -> String
+primStringShow :: String -> String
+primReturn :: a -> IO a
+primBind :: IO a -> (a -> IO b) -> IO b
+primPutStr :: String -> IO ()
+primGetLine :: IO String
+primGetContents :: IO String
+primSeq :: a -> b -> b
+
+instance Eq Int where
+  (==) = primIntEq
+instance Ord Int where
+  (<) = primIntLt
+instance Show Int where
+  show = primIntShow
+instance Num Int where
+  (+) = primIntAdd
+  (-) = primIntSub
+  (*) = primIntMul
+  negate = primIntNegate
+  abs = primIntAbs
+  signum = primIntSignum
+  fromInteger = primIntFromInteger
+instance Integral Int where
+  div = primIntDiv
+  mod = primIntMod
+  quot = primIntQuot
+  rem = primIntRem
+  toInteger = primIntToInteger
+
+instance Eq Integer where
+  (==) = primIntegerEq
+instance Ord Integer where
+  (<) = primIntegerLt
+instance Show Integer where
+  show = primIntegerShow
+instance Num Integer where
+  (+) = primIntegerAdd
+  (-) = primIntegerSub
+  (*) = primIntegerMul
+  negate = primIntegerNegate
+  abs = primIntegerAbs
+  signum = primIntegerSignum
+  fromInteger x = x
+instance Integral Integer where
+  div = primIntegerDiv
+  mod = primIntegerMod
+  quot = primIntegerQuot
+  rem = primIntegerRem
+  toInteger x = x
+
+instance Enum Integer
+
+fromIntegral :: (Integral a, Num b) => a -> b
+fromIntegral x = fromInteger (toInteger x)
+
+instance Eq Double where
+  (==) = primDoubleEq
+instance Ord Double where
+  (<) = primDoubleLt
+  (<=) = primDoubleLe
+  (>) = primDoubleGt
+  (>=) = primDoubleGe
+instance Show Double where
+  show = primDoubleShow
+instance Num Double where
+  (+) = primDoubleAdd
+  (-) = primDoubleSub
+  (*) = primDoubleMul
+  negate = primDoubleNegate
+  abs = primDoubleAbs
+  signum = primDoubleSignum
+  fromInteger = primDoubleFromInteger
+instance Fractional Double where
+  (/) = primDoubleDiv
+  fromDouble x = x
+instance Floating Double where
+  pi = 3.141592653589793
+  exp = primDoubleExp
+  log = primDoubleLog
+  sqrt = primDoubleSqrt
+  sin = primDoubleSin
+  cos = primDoubleCos
+  tan = primDoubleTan
+  asin = primDoubleAsin
+  acos = primDoubleAcos
+  atan = primDoubleAtan
+  (**) = primDoublePow
+
+truncate :: Integral b => Double -> b
+truncate x = fromInteger (primDoubleTruncate x)
+
+round :: Integral b => Double -> b
+round x = fromInteger (primDoubleRound x)
+
+floor :: Integral b => Double -> b
+floor x = fromInteger (primDoubleFloor x)
+
+ceiling :: Integral b => Double -> b
+ceiling x = fromInteger (primDoubleCeiling x)
+
+(^) :: (Num a, Integral b) => a -> b -> a
+(^) x n = power k where { k = primIntegerExponent (toInteger n); power e | e == 0 = 1 | e `mod` 2 == 0 = h * h | otherwise = x * h * h where { h = power half; half = e `div` 2 } }
+
+instance Enum Int
+
+instance Eq Bool where
+  (==) = primBoolEq
+instance Ord Bool where
+  (<) = primBoolLt
+instance Show Bool where
+  show = primBoolShow
+
+instance Eq Char where
+  (==) = primCharEq
+instance Ord Char where
+  (<) = primCharLt
+instance Show Char where
+  show = primCharShow
+  showList = primStringShow
+
+instance Enum Char
+
+instance Eq a => Eq [a] where
+  (==) [] [] = True
+  (==) (x:xs) (y:ys) = (x == y) && (xs == ys)
+  (==) xs ys = False
+
+instance Ord a => Ord [a] where
+  (<) [] [] = False
+  (<) [] (y:ys) = True
+  (<) (x:xs) [] = False
+  (<) (x:xs) (y:ys) = (x < y) || ((x == y) && (xs < ys))
+
+instance Show a => Show [a] where
+  show = showList
+
+instance Eq () where
+  (==) x y = True
+instance Show () where
+  show x = "()"
+
+instance (Eq a, Eq b) => Eq (a, b) where
+  (==) (a, b) (c, d) = (a == c) && (b == d)
+instance (Show a, Show b) => Show (a, b) where
+  show (a, b) = "(" ++ show a ++ "," ++ show b ++ ")"
+
+instance (Eq a, Eq b, Eq c) => Eq (a, b, c) where
+  (==) (a, b, c) (d, e, f) = (a == d) && (b == e) && (c == f)
+instance (Show a, Show b, Show c) => Show (a, b, c) where
+  show (a, b, c) = "(" ++ show a ++ "," ++ show b ++ "," ++ show c ++ ")"
+
+return :: a -> IO a
+return = primReturn
+
+(>>=) :: IO a -> (a -> IO b) -> IO b
+(>>=) = primBind
+
+(>>) :: IO a -> IO b -> IO b
+(>>) m k = m >>= \_ -> k
+
+putStr :: String -> IO ()
+putStr = primPutStr
+
+putStrLn :: String -> IO ()
+putStrLn s = putStr s >> putStr "\n"
+
+print :: Show a => a -> IO ()
+print x = putStrLn (show x)
+
+getLine :: IO String
+getLine = primGetLine
+
+getContents :: IO String
+getContents = primGetContents
+
+seq :: a -> b -> b
+seq = primSeq
+
+($!) :: (a -> b) -> a -> b
+f $! x = x `seq` f x
+
+interact :: (String -> String) -> IO ()
+interact f = getContents >>= \s -> putStr (f s)
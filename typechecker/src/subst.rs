@@ -55,17 +55,6 @@ impl Substitution {
         Substitution { map }
     }
 
-    pub fn range(self) -> HashSet<String> {
-        let mut res = HashSet::new();
-        for (_, x) in self.map {
-            match x {
-                Type::TypeVariable(tvar) => {res.insert(tvar);},
-                _ => unreachable!(),
-            }
-        }
-        res
-    }
-
     pub fn exclude(&self, scheme_vars: &HashSet<String>) -> Substitution {
         let mut map = self.map.clone();
         scheme_vars.iter().for_each(|var| {
@@ -74,11 +63,3 @@ impl Substitution {
         Substitution { map }
     }
 }
-
-pub fn subst_combine(left: Substitution, right: Substitution) -> Substitution {
-    let mut map = right.map;
-    for (k, v) in left.map {
-        map.insert(k, v);
-    }
-    Substitution { map }
-}
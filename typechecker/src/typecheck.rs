@@ -4,296 +4,1372 @@ use std::{
 };
 
 use crate::{
+    class::{
+        default_method, dictionary_name, selector, superclass_selector, type_head, Class,
+        ClassEnvironment, Instance,
+    },
     error::TypingError,
-    subst::{subst_combine, Substitution},
-    util::{fresh_name, scvs_given_te, scvs_in_type_signature, sub_type, sub_type_env},
+    kind::{resolve_type, Kind, KindEnvironment},
+    subst::Substitution,
+    util::{
+        dependency_order, fresh_name, is_rigid, replace_decl, rigid_name, scvs_given_te,
+        scvs_in_type_signature, sub_type, sub_type_env, tvars_in, unknowns_te, vars_in,
+    },
+};
+use ast::ast::{
+    Constraint, Decl, Expr, Guard, List, Literal, Module, Op, Pattern, Program, Rhs, Type,
 };
-use ast::ast::{Decl, Expr, List, Literal, Op, Pattern, Program, Type};
 use log::info;
+use num_bigint::BigInt;
 
-pub type TypeScheme = (HashSet<String>, Type);
+/// A class constraint `C t` on a type
+pub type Predicate = (String, Type);
+pub type TypeScheme = (HashSet<String>, Vec<Predicate>, Type);
 pub type TypingEnvironment = HashMap<String, TypeScheme>;
+/// A function binding: its name, its arguments and its body
+type Binding<'a> = (&'a str, &'a [String], &'a Expr);
+/// The type scheme and elaborated body inferred for a binding
+type Inferred = (TypeScheme, Expr);
 
-pub fn typecheck_program(p: &Program) -> Result<Substitution, TypingError> {
-    let mut type_env = TypingEnvironment::new();
-    let mut subst = Substitution::id_subst();
-    for decl in p {
-        subst = typecheck_decl(&mut type_env, subst, decl)?;
+const PRELUDE: &str = include_str!("prelude.hs");
+
+/// The types to try, in order, for an ambiguous type variable
+const DEFAULT_TYPES: [Type; 2] = [Type::Integer, Type::Double];
+
+/// Everything the typechecker learns while walking the program besides the types of bindings
+#[derive(Default)]
+struct Context {
+    kinds: KindEnvironment,
+    /// The types type aliases stand for, by name
+    aliases: HashMap<String, Type>,
+    classes: ClassEnvironment,
+    signatures: HashMap<String, TypeScheme>,
+    /// Predicates still waiting for a dictionary, with the variable standing in for it
+    pending: Vec<(String, Predicate)>,
+    /// The dictionaries found for those variables
+    dictionaries: HashMap<String, Expr>,
+    /// Bindings without a signature that are being checked, with their monomorphic type variable
+    /// and the variables standing in for their recursive uses
+    recursive: HashMap<String, (String, Vec<String>)>,
+}
+
+/// How predicates on the type variables a binding is generalised over are discharged
+enum Discharge<'a> {
+    /// Abstract over them with dictionary parameters
+    Abstract,
+    /// They must follow from the dictionaries given by a signature or instance context
+    Given(&'a [(Predicate, Expr)]),
+    /// Default the type variables, as nothing can be abstracted over
+    Default,
+}
+
+/// Typechecks the program after the prelude and elaborates both into a program where every use
+/// of an overloaded function is passed its class dictionaries explicitly.
+pub fn typecheck_program(p: &Program) -> Result<Program, TypingError> {
+    let main = Module {
+        name: "Main".to_string(),
+        exports: None,
+        imports: vec![],
+        program: p.clone(),
+    };
+    typecheck_modules(&[main])
+}
+
+/// Typechecks modules whose names are resolved, each after the modules it imports, and elaborates
+/// them after the prelude into a single program. The type schemes a module exports are reused by
+/// the modules importing it. Types, classes, constructors and instances are shared by all modules.
+pub fn typecheck_modules(modules: &[Module]) -> Result<Program, TypingError> {
+    let prelude = parser::parse(PRELUDE).expect("The prelude must parse");
+    let mut ctx = Context::default();
+    let mut program = vec![];
+    // The prelude and the constructors and methods of the modules checked so far
+    let mut shared = TypingEnvironment::new();
+    let mut subst = typecheck_source(
+        &mut ctx,
+        &mut shared,
+        Substitution::id_subst(),
+        &prelude,
+        &mut program,
+    )?;
+    let mut exports: HashMap<&str, TypingEnvironment> = HashMap::new();
+    // Types, constructors and classes are shared by all modules, with the module defining each
+    let mut owners: HashMap<String, &str> = HashMap::new();
+    let mut type_env = shared.clone();
+    for module in modules {
+        for name in type_level_names(&module.program) {
+            match owners.insert(name.clone(), &module.name) {
+                Some(owner) if owner != module.name => {
+                    let (first, second) = (owner.to_string(), module.name.clone());
+                    return Err(TypingError::DefinedByModules(name, first, second));
+                }
+                _ => {}
+            }
+        }
+        type_env = shared.clone();
+        for import in &module.imports {
+            if let Some(exported) = exports.get(import.module.as_str()) {
+                type_env.extend(exported.clone());
+            }
+        }
+        let in_scope = type_env.keys().cloned().collect::<HashSet<_>>();
+        subst = typecheck_source(
+            &mut ctx,
+            &mut type_env,
+            subst,
+            &module.program,
+            &mut program,
+        )?;
+        let values = module
+            .program
+            .iter()
+            .filter_map(|decl| match decl {
+                Decl::FunDecl(name, _, _) => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (name, scheme) in &type_env {
+            if !in_scope.contains(name) && !values.contains(name) {
+                shared.insert(name.clone(), scheme.clone());
+            }
+        }
+        let exported = module
+            .exports
+            .as_ref()
+            .unwrap_or(&values)
+            .iter()
+            .filter_map(|name| Some((name.clone(), type_env.get(name)?.clone())))
+            .collect();
+        exports.insert(&module.name, exported);
+    }
+    // `main` is run as the program's action
+    if type_env.contains_key("main") {
+        let mark = ctx.pending.len();
+        let main = Expr::Var("main".to_string());
+        let (subst, t, _) = typecheck_expression(&mut ctx, &mut type_env, subst, &main)?;
+        let action = Type::IO(Box::new(Type::TypeVariable(fresh_name())));
+        let subst = unify(subst, &t, &action)?;
+        discharge_predicates(
+            &mut ctx,
+            subst,
+            &type_env,
+            mark,
+            &action,
+            Discharge::Default,
+        )?;
+    }
+    Ok(program)
+}
+
+/// The types, constructors and classes a program defines
+fn type_level_names(program: &Program) -> Vec<String> {
+    let mut names = vec![];
+    for decl in program {
+        match decl {
+            Decl::DataDecl(name, _, constructors) => {
+                names.push(name.clone());
+                names.extend(
+                    constructors
+                        .iter()
+                        .map(|(constructor, _)| constructor.clone()),
+                );
+            }
+            Decl::TypeAlias(name, _) | Decl::ClassDecl(_, name, _, _) => names.push(name.clone()),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// Typechecks the declarations of a source file, adding them elaborated to `program`
+fn typecheck_source(
+    ctx: &mut Context,
+    type_env: &mut TypingEnvironment,
+    mut subst: Substitution,
+    source: &Program,
+    program: &mut Program,
+) -> Result<Substitution, TypingError> {
+    declare_types(ctx, source)?;
+    ctx.signatures.clear();
+    for decl in source {
+        let (phi, decls) = typecheck_decl(ctx, type_env, subst, decl)?;
+        subst = phi;
+        program.extend(
+            decls
+                .into_iter()
+                .map(|decl| replace_decl(decl, &ctx.dictionaries)),
+        );
     }
     Ok(subst)
 }
 
+/// Data types and instances scope over the whole program, so they are collected upfront
+fn declare_types(ctx: &mut Context, p: &Program) -> Result<(), TypingError> {
+    // Types and classes share one namespace and constructors another
+    let mut types = HashSet::new();
+    let mut constructors = HashSet::new();
+    for decl in p {
+        let (name, data_constructors) = match decl {
+            Decl::DataDecl(name, _, data_constructors) => (name, &data_constructors[..]),
+            Decl::TypeAlias(name, _) | Decl::ClassDecl(_, name, _, _) => (name, &[][..]),
+            _ => continue,
+        };
+        if !types.insert(name) {
+            return Err(TypingError::MultipleDeclarations(name.clone()));
+        }
+        for (constructor, _) in data_constructors {
+            if !constructors.insert(constructor) {
+                return Err(TypingError::MultipleDeclarations(constructor.clone()));
+            }
+        }
+    }
+    for decl in p {
+        if let Decl::DataDecl(name, params, _) = decl {
+            ctx.kinds.insert(name.clone(), Kind::of_arity(params.len()));
+        }
+    }
+    for decl in p {
+        if let Decl::InstanceDecl(context, class, head, _) = decl {
+            let head = resolve_type(&ctx.kinds, &ctx.aliases, head)?;
+            let (head_name, args) =
+                type_head(&head).ok_or(TypingError::InvalidInstanceHead(head.clone()))?;
+            let mut params = vec![];
+            for arg in args {
+                match arg {
+                    Type::TypeVariable(param) if !params.contains(&param) => params.push(param),
+                    _ => return Err(TypingError::InvalidInstanceHead(head)),
+                }
+            }
+            if let Some((_, var)) = context.iter().find(|(_, var)| !params.contains(var)) {
+                return Err(TypingError::UnboundTypeVariable(var.clone()));
+            }
+            let instance = Instance {
+                params,
+                context: context.clone(),
+            };
+            let key = (class.clone(), head_name);
+            if ctx.classes.instances.insert(key, instance).is_some() {
+                return Err(TypingError::DuplicateInstance(class.clone(), head));
+            }
+        }
+    }
+    Ok(())
+}
+
 fn typecheck_decl(
+    ctx: &mut Context,
     type_env: &mut TypingEnvironment,
     subst: Substitution,
     decl: &Decl,
-) -> Result<Substitution, TypingError> {
+) -> Result<(Substitution, Vec<Decl>), TypingError> {
     match decl {
+        Decl::DataDecl(name, params, constructors) => {
+            info!("Introducing data type {} {}", name, params.join(" "));
+            let mut scheme_vars = HashSet::new();
+            for param in params {
+                if !scheme_vars.insert(param.clone()) {
+                    return Err(TypingError::DuplicateTypeVariable(param.clone()));
+                }
+            }
+            let data_type = Type::Constructor(
+                name.clone(),
+                params.iter().cloned().map(Type::TypeVariable).collect(),
+            );
+            for (constructor, fields) in constructors {
+                let fields = fields
+                    .iter()
+                    .map(|field| resolve_type(&ctx.kinds, &ctx.aliases, field))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if let Some(tv) = fields
+                    .iter()
+                    .flat_map(tvars_in)
+                    .find(|tv| !scheme_vars.contains(*tv))
+                {
+                    return Err(TypingError::UnboundTypeVariable(tv.clone()));
+                }
+                let constructor_type = fields
+                    .into_iter()
+                    .rev()
+                    .fold(data_type.clone(), |acc, field| {
+                        Type::Function(Box::new(field), Box::new(acc))
+                    });
+                info!("Constructor {} :: {}", constructor, constructor_type);
+                type_env.insert(
+                    constructor.clone(),
+                    (scheme_vars.clone(), vec![], constructor_type),
+                );
+            }
+            Ok((subst, vec![]))
+        }
         Decl::TypeAlias(var_name, type1) => {
             info!("Introducing type alias {} = {}", var_name, type1);
-            subst.extended(var_name.clone(), type1.clone())
+            let type1 = resolve_type(&ctx.kinds, &ctx.aliases, type1)?;
+            ctx.aliases.insert(var_name.clone(), type1);
+            Ok((subst, vec![]))
         }
-        Decl::TypeSignature(var_name, type1) => {
+        Decl::TypeSignature(var_name, context, type1) => {
             info!("Introducing type signature {} :: {}", var_name, type1);
-            let schematic_vars = scvs_in_type_signature(type1);
-            if let Some((scvs2, type2)) =
-                type_env.insert(var_name.clone(), (schematic_vars.clone(), type1.clone()))
-            {
-                let map1 = schematic_vars
-                    .into_iter()
-                    .map(|name| (name, Type::TypeVariable(fresh_name())))
-                    .collect::<HashMap<String, Type>>();
-                let map2 = scvs2
-                    .into_iter()
-                    .map(|name| (name, Type::TypeVariable(fresh_name())))
-                    .collect::<HashMap<String, Type>>();
-                let subst1 = Substitution::from(map1);
-                let subst2 = Substitution::from(map2);
-                return unify(subst, &sub_type(&subst1, type1), &sub_type(&subst2, &type2));
-            }
-            Ok(subst)
+            let type1 = resolve_type(&ctx.kinds, &ctx.aliases, type1)?;
+            let predicates = check_context(ctx, context, &type1)?;
+            let scheme = (scvs_in_type_signature(&type1), predicates, type1);
+            ctx.signatures.insert(var_name.clone(), scheme.clone());
+            type_env.insert(var_name.clone(), scheme);
+            Ok((subst, vec![]))
         }
         Decl::FunDecl(name, vars, expr) => {
             info!("Type checking {name} with arguments {}", vars.join(", "));
-            let mut expr_env = type_env.clone();
-            if !expr_env.contains_key(name) {
-                let fresh = fresh_name();
-                expr_env.insert(name.clone(), (HashSet::new(), Type::TypeVariable(fresh)));
+            match ctx.signatures.get(name).cloned() {
+                Some(scheme) => {
+                    let (subst, decl) =
+                        check_signature(ctx, type_env, subst, (name, vars, expr), &scheme)?;
+                    type_env.insert(name.clone(), scheme);
+                    Ok((subst, vec![decl]))
+                }
+                None => {
+                    let (subst, scheme, params, expr) =
+                        infer_binding(ctx, type_env, subst, name, vars, expr)?;
+                    info!(
+                        "Resulting in fun_type [{}], {}",
+                        scheme.0.iter().cloned().collect::<Vec<_>>().join(", "),
+                        scheme.2
+                    );
+                    type_env.insert(name.clone(), scheme);
+                    let args = vars.iter().chain(params.iter().rev()).cloned().collect();
+                    Ok((subst, vec![Decl::FunDecl(name.clone(), args, expr)]))
+                }
             }
-            let arg_renamings = vars
-                .into_iter()
-                .map(|arg| (arg, fresh_name()))
+        }
+        Decl::ClassDecl(superclasses, name, var, decls) => {
+            info!("Introducing class {} {}", name, var);
+            let mut subst = subst;
+            for (superclass, superclass_var) in superclasses {
+                if !ctx.classes.classes.contains_key(superclass) {
+                    return Err(TypingError::UnknownClass(superclass.clone()));
+                }
+                if superclass_var != var {
+                    return Err(TypingError::UnboundTypeVariable(superclass_var.clone()));
+                }
+            }
+            let mut methods = vec![];
+            for decl in decls {
+                if let Decl::TypeSignature(method, context, method_type) = decl {
+                    let method_type = resolve_type(&ctx.kinds, &ctx.aliases, method_type)?;
+                    let class_predicate = (name.clone(), Type::TypeVariable(var.clone()));
+                    let mut predicates = vec![class_predicate];
+                    predicates.extend(check_context(ctx, context, &method_type)?);
+                    let mut scheme_vars = scvs_in_type_signature(&method_type);
+                    scheme_vars.insert(var.clone());
+                    type_env.insert(
+                        method.clone(),
+                        (scheme_vars, predicates, method_type.clone()),
+                    );
+                    methods.push((method.clone(), method_type));
+                }
+            }
+            let superclasses = superclasses
+                .iter()
+                .map(|(superclass, _)| superclass.clone())
                 .collect::<Vec<_>>();
-            for (var, fresh) in arg_renamings.clone() {
-                expr_env.insert(var.clone(), (HashSet::new(), Type::TypeVariable(fresh)));
+            let defaults = decls
+                .iter()
+                .filter_map(|decl| match decl {
+                    Decl::FunDecl(method, _, _) => Some(method.clone()),
+                    _ => None,
+                })
+                .collect::<HashSet<_>>();
+            if let Some(method) = defaults
+                .iter()
+                .find(|m| !methods.iter().any(|(n, _)| n == *m))
+            {
+                return Err(TypingError::UnknownMethod(name.clone(), method.clone()));
+            }
+            ctx.classes.classes.insert(
+                name.clone(),
+                Class {
+                    superclasses: superclasses.clone(),
+                    var: var.clone(),
+                    methods: methods.clone(),
+                    defaults,
+                },
+            );
+
+            // A dictionary is a tuple of the superclass dictionaries followed by the methods
+            let size = superclasses.len() + methods.len();
+            let mut elaborated = vec![];
+            for (i, superclass) in superclasses.iter().enumerate() {
+                let (args, body) = selector(size, i);
+                elaborated.push(Decl::FunDecl(
+                    superclass_selector(name, superclass),
+                    args,
+                    body,
+                ));
+            }
+            for (i, (method, _)) in methods.iter().enumerate() {
+                let (args, body) = selector(size, superclasses.len() + i);
+                elaborated.push(Decl::FunDecl(method.clone(), args, body));
+            }
+            for decl in decls {
+                if let Decl::FunDecl(method, vars, expr) = decl {
+                    let scheme = type_env[method].clone();
+                    let (expected, givens, params) =
+                        instantiate_signature(&scheme, &HashMap::new());
+                    let (phi, expr) =
+                        check_binding(ctx, type_env, subst, vars, expr, &expected, &givens)?;
+                    subst = phi;
+                    let args = vars.iter().chain(params.iter().rev()).cloned().collect();
+                    elaborated.push(Decl::FunDecl(default_method(name, method), args, expr));
+                }
+            }
+            Ok((subst, elaborated))
+        }
+        Decl::InstanceDecl(context, class_name, head, decls) => {
+            info!("Introducing instance {} {}", class_name, head);
+            let class = ctx
+                .classes
+                .classes
+                .get(class_name)
+                .cloned()
+                .ok_or(TypingError::UnknownClass(class_name.clone()))?;
+            if let Some((context_class, _)) = context
+                .iter()
+                .find(|(c, _)| !ctx.classes.classes.contains_key(c))
+            {
+                return Err(TypingError::UnknownClass(context_class.clone()));
             }
-            let (return_subst, return_type) = typecheck_expression(&mut expr_env, expr)?;
-            let subst = subst_combine(subst, return_subst);
-            let fun_type = arg_renamings
+            let head = resolve_type(&ctx.kinds, &ctx.aliases, head)?;
+            let (head_name, _) =
+                type_head(&head).ok_or(TypingError::InvalidInstanceHead(head.clone()))?;
+            let dictionary = dictionary_name(class_name, &head_name);
+
+            // The instance is checked like a function of the dictionaries its context asks for
+            let head_vars = tvars_in(&head)
                 .into_iter()
-                .map(|(_, arg_type)| arg_type)
-                .fold(return_type, |acc, arg_type| {
-                    Type::Function(Box::new(Type::TypeVariable(arg_type)), Box::new(acc))
+                .map(|var| (var.clone(), Type::TypeVariable(fresh_name())))
+                .collect::<HashMap<_, _>>();
+            let head = sub_type(&Substitution::from(head_vars.clone()), &head);
+            let params = context
+                .iter()
+                .map(|_| format!("dict{}", fresh_name()))
+                .collect::<Vec<_>>();
+            let givens = zip(context, &params)
+                .map(|((class, var), param)| {
+                    (
+                        (class.clone(), head_vars[var].clone()),
+                        Expr::Var(param.clone()),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let this = params
+                .iter()
+                .fold(Expr::Var(dictionary.clone()), |acc, param| {
+                    Expr::Application(Box::new(acc), Box::new(Expr::Var(param.clone())))
                 });
-            let fun_type = sub_type(&subst, &fun_type);
-            let scvs = scvs_given_te(&fun_type, &type_env);
-            info!(
-                "Resulting in fun_type [{}], {}",
-                scvs.iter().cloned().collect::<Vec<_>>().join(", "),
-                fun_type
-            );
-            if let Some((_, saved_type)) = type_env.get(name) {
-                return unify(subst, &saved_type, &fun_type);
-            } else {
-                type_env.insert(name.to_string(), (scvs, fun_type));
+
+            let mut fields = vec![];
+            let mark = ctx.pending.len();
+            for superclass in &class.superclasses {
+                let placeholder = format!("dict{}", fresh_name());
+                ctx.pending
+                    .push((placeholder.clone(), (superclass.clone(), head.clone())));
+                fields.push(Expr::Var(placeholder));
+            }
+            let (mut subst, _) =
+                discharge_predicates(ctx, subst, type_env, mark, &head, Discharge::Given(&givens))?;
+
+            for decl in decls {
+                if let Decl::FunDecl(method, _, _) = decl {
+                    if !class.methods.iter().any(|(m, _)| m == method) {
+                        return Err(TypingError::UnknownMethod(
+                            class_name.clone(),
+                            method.clone(),
+                        ));
+                    }
+                }
+            }
+            for (method, _) in &class.methods {
+                let implementation = decls.iter().find_map(|decl| match decl {
+                    Decl::FunDecl(name, vars, expr) if name == method => Some((vars, expr)),
+                    _ => None,
+                });
+                let field = match implementation {
+                    Some((vars, expr)) => {
+                        let scheme = type_env[method].clone();
+                        let mut instantiation = HashMap::new();
+                        instantiation.insert(class.var.clone(), head.clone());
+                        let (expected, mut method_givens, method_params) =
+                            instantiate_signature(&scheme, &instantiation);
+                        // The first predicate is the class itself, which the instance provides
+                        method_givens.remove(0);
+                        method_givens.extend(givens.iter().cloned());
+                        let (phi, expr) = check_binding(
+                            ctx,
+                            type_env,
+                            subst,
+                            vars,
+                            expr,
+                            &expected,
+                            &method_givens,
+                        )?;
+                        subst = phi;
+                        let expr = vars
+                            .iter()
+                            .fold(expr, |acc, var| Expr::Lambda(var.clone(), Box::new(acc)));
+                        method_params.iter().skip(1).rev().fold(expr, |acc, param| {
+                            Expr::Lambda(param.clone(), Box::new(acc))
+                        })
+                    }
+                    None if class.defaults.contains(method) => Expr::Application(
+                        Box::new(Expr::Var(default_method(class_name, method))),
+                        Box::new(this.clone()),
+                    ),
+                    None => {
+                        return Err(TypingError::MissingMethod(
+                            class_name.clone(),
+                            method.clone(),
+                        ))
+                    }
+                };
+                fields.push(field);
             }
-            Ok(subst)
+            let args = params.into_iter().rev().collect();
+            Ok((
+                subst,
+                vec![Decl::FunDecl(dictionary, args, Expr::Tuple(fields))],
+            ))
+        }
+        Decl::SExpr(e) => {
+            let mark = ctx.pending.len();
+            let (subst, t, e) = typecheck_expression(ctx, type_env, subst, e)?;
+            let (subst, _) =
+                discharge_predicates(ctx, subst, type_env, mark, &t, Discharge::Default)?;
+            Ok((subst, vec![Decl::SExpr(e)]))
         }
-        Decl::SExpr(e) => typecheck_expression(type_env, e).map(|(subst, _)| subst),
-        Decl::EndOfInstruction => Ok(Substitution::id_subst()),
+        Decl::EndOfInstruction => Ok((subst, vec![Decl::EndOfInstruction])),
+    }
+}
+
+/// Checks the classes in the context of a signature and turns it into predicates
+fn check_context(
+    ctx: &Context,
+    context: &[Constraint],
+    t: &Type,
+) -> Result<Vec<Predicate>, TypingError> {
+    let vars = tvars_in(t);
+    context
+        .iter()
+        .map(|(class, var)| {
+            let predicate = (class.clone(), Type::TypeVariable(var.clone()));
+            if !ctx.classes.classes.contains_key(class) {
+                return Err(TypingError::UnknownClass(class.clone()));
+            }
+            if !vars.contains(&var) {
+                return Err(TypingError::AmbiguousType(predicate.0, predicate.1));
+            }
+            Ok(predicate)
+        })
+        .collect()
+}
+
+/// Instantiates a signature with fresh type variables, except for those fixed by `fixed`, and
+/// introduces a dictionary parameter for each of its predicates.
+fn instantiate_signature(
+    scheme: &TypeScheme,
+    fixed: &HashMap<String, Type>,
+) -> (Type, Vec<(Predicate, Expr)>, Vec<String>) {
+    let (scheme_vars, predicates, t) = scheme;
+    let map = scheme_vars
+        .iter()
+        .map(|var| match fixed.get(var) {
+            Some(t) => (var.clone(), t.clone()),
+            None => (var.clone(), Type::TypeVariable(fresh_name())),
+        })
+        .collect();
+    let phi = Substitution::from(map);
+    let params = predicates
+        .iter()
+        .map(|_| format!("dict{}", fresh_name()))
+        .collect::<Vec<_>>();
+    let givens = zip(predicates, &params)
+        .map(|((class, t), param)| ((class.clone(), sub_type(&phi, t)), Expr::Var(param.clone())))
+        .collect();
+    (sub_type(&phi, t), givens, params)
+}
+
+/// Checks a function binding against its signature and elaborates it into a function taking the
+/// dictionaries the signature asks for.
+fn check_signature(
+    ctx: &mut Context,
+    type_env: &TypingEnvironment,
+    subst: Substitution,
+    (name, vars, expr): Binding,
+    scheme: &TypeScheme,
+) -> Result<(Substitution, Decl), TypingError> {
+    let (expected, givens, params) = instantiate_signature(scheme, &HashMap::new());
+    let (subst, expr) = check_binding(ctx, type_env, subst, vars, expr, &expected, &givens)?;
+    let args = vars.iter().chain(params.iter().rev()).cloned().collect();
+    Ok((subst, Decl::FunDecl(name.to_string(), args, expr)))
+}
+
+/// Checks a function binding against the type of its signature, with the dictionaries `givens`
+/// in scope. Returns the elaborated body.
+fn check_binding(
+    ctx: &mut Context,
+    type_env: &TypingEnvironment,
+    subst: Substitution,
+    vars: &[String],
+    expr: &Expr,
+    expected: &Type,
+    givens: &[(Predicate, Expr)],
+) -> Result<(Substitution, Expr), TypingError> {
+    let mark = ctx.pending.len();
+    let mut expr_env = type_env.clone();
+    let (subst, fun_type, expr) = typecheck_function(ctx, &mut expr_env, subst, vars, expr)?;
+    let subst = unify(subst, expected, &fun_type)?;
+    let (subst, _) = discharge_predicates(
+        ctx,
+        subst,
+        type_env,
+        mark,
+        expected,
+        Discharge::Given(givens),
+    )?;
+    Ok((subst, expr))
+}
+
+/// Infers the type of a function binding without a signature and generalises it, abstracting over
+/// the class predicates it needs. Returns the type scheme, the dictionary parameters and the
+/// elaborated body.
+fn infer_binding(
+    ctx: &mut Context,
+    type_env: &TypingEnvironment,
+    subst: Substitution,
+    name: &str,
+    vars: &[String],
+    expr: &Expr,
+) -> Result<(Substitution, TypeScheme, Vec<String>, Expr), TypingError> {
+    let (subst, mut inferred, params) =
+        infer_bindings(ctx, type_env, subst, &[(name, vars, expr)])?;
+    let (scheme, expr) = inferred.remove(0);
+    Ok((subst, scheme, params, expr))
+}
+
+/// Infers the types of mutually recursive bindings together and generalises them. They share the
+/// class predicates they abstract over, so every binding takes the same dictionary parameters.
+/// Returns the type scheme and elaborated body of every binding and the dictionary parameters.
+fn infer_bindings(
+    ctx: &mut Context,
+    type_env: &TypingEnvironment,
+    subst: Substitution,
+    bindings: &[Binding],
+) -> Result<(Substitution, Vec<Inferred>, Vec<String>), TypingError> {
+    let mark = ctx.pending.len();
+    let mut expr_env = type_env.clone();
+    let mut mono_types = vec![];
+    let mut shadowed = vec![];
+    for (name, _, _) in bindings {
+        let fresh = fresh_name();
+        let mono_type = Type::TypeVariable(fresh.clone());
+        expr_env.insert(
+            name.to_string(),
+            (HashSet::new(), vec![], mono_type.clone()),
+        );
+        shadowed.push(ctx.recursive.insert(name.to_string(), (fresh, vec![])));
+        mono_types.push(mono_type);
+    }
+    let result = zip(bindings, &mono_types).try_fold(
+        (subst, vec![]),
+        |(subst, mut exprs), ((_, vars, expr), mono_type)| {
+            let (subst, fun_type, expr) =
+                typecheck_function(ctx, &mut expr_env.clone(), subst, vars, expr)?;
+            let subst = unify(subst, mono_type, &fun_type)?;
+            exprs.push(expr);
+            Ok((subst, exprs))
+        },
+    );
+    let mut recursive_uses = vec![];
+    for ((name, _, _), shadowed) in zip(bindings, shadowed) {
+        let (_, uses) = ctx.recursive.remove(*name).unwrap_or_default();
+        recursive_uses.push(uses);
+        if let Some(shadowed) = shadowed {
+            ctx.recursive.insert(name.to_string(), shadowed);
+        }
+    }
+    let (subst, exprs) = result?;
+    let group_type = Type::Tuple(mono_types.clone());
+    let (subst, abstracted) =
+        discharge_predicates(ctx, subst, type_env, mark, &group_type, Discharge::Abstract)?;
+    let env = sub_type_env(&subst, type_env);
+    let (predicates, params): (Vec<_>, Vec<_>) = abstracted.into_iter().unzip();
+    let inferred = zip(&mono_types, exprs)
+        .map(|(mono_type, expr)| {
+            let fun_type = sub_type(&subst, mono_type);
+            let scheme = (scvs_given_te(&fun_type, &env), predicates.clone(), fun_type);
+            (scheme, expr)
+        })
+        .collect();
+    for ((name, _, _), uses) in zip(bindings, recursive_uses) {
+        let recursive_call = params
+            .iter()
+            .fold(Expr::Var(name.to_string()), |acc, param| {
+                Expr::Application(Box::new(acc), Box::new(Expr::Var(param.clone())))
+            });
+        for placeholder in uses {
+            ctx.dictionaries.insert(placeholder, recursive_call.clone());
+        }
+    }
+    Ok((subst, inferred, params))
+}
+
+/// Typechecks the bindings of a `let` or `where` block, adding them to `type_env`. Bindings with a
+/// signature have their declared type in all others and are checked last. The rest are generalised
+/// one group of mutually recursive bindings at a time, so a binding is polymorphic in the bindings
+/// using it.
+fn typecheck_bindings(
+    ctx: &mut Context,
+    type_env: &mut TypingEnvironment,
+    subst: Substitution,
+    decls: &[Decl],
+) -> Result<(Substitution, Vec<Decl>), TypingError> {
+    let mut signatures = HashMap::new();
+    for decl in decls {
+        if let Decl::TypeSignature(name, context, t) = decl {
+            let t = resolve_type(&ctx.kinds, &ctx.aliases, t)?;
+            let predicates = check_context(ctx, context, &t)?;
+            signatures.insert(name.clone(), (scvs_in_type_signature(&t), predicates, t));
+        }
+    }
+    let bindings = decls
+        .iter()
+        .filter_map(|decl| match decl {
+            Decl::FunDecl(name, vars, expr) => Some((name.as_str(), &vars[..], expr)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if let Some(name) = signatures
+        .keys()
+        .find(|name| !bindings.iter().any(|(bound, _, _)| bound == name))
+    {
+        return Err(TypingError::MissingBinding(name.clone()));
+    }
+    type_env.extend(signatures.clone());
+    let (declared, inferred): (Vec<_>, Vec<_>) = bindings
+        .into_iter()
+        .partition(|(name, _, _)| signatures.contains_key(*name));
+    let uses = inferred
+        .iter()
+        .map(|(_, vars, expr)| {
+            let used = vars_in(expr);
+            (0..inferred.len())
+                .filter(|&i| {
+                    let (name, _, _) = &inferred[i];
+                    used.contains(&name.to_string()) && !vars.iter().any(|var| var == name)
+                })
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    let mut subst = subst;
+    let mut elaborated = vec![];
+    for component in dependency_order(&uses) {
+        let group = component.iter().map(|&i| inferred[i]).collect::<Vec<_>>();
+        let (phi, schemes, params) = infer_bindings(ctx, type_env, subst, &group)?;
+        subst = phi;
+        for ((name, vars, _), (scheme, expr)) in zip(group, schemes) {
+            info!("Local binding {} :: {}", name, scheme.2);
+            type_env.insert(name.to_string(), scheme);
+            let args = vars.iter().chain(params.iter().rev()).cloned().collect();
+            elaborated.push(Decl::FunDecl(name.to_string(), args, expr));
+        }
+    }
+    for binding @ (name, _, _) in declared {
+        let (phi, decl) = check_signature(ctx, type_env, subst, binding, &signatures[name])?;
+        subst = phi;
+        elaborated.push(decl);
+    }
+    Ok((subst, elaborated))
+}
+
+fn typecheck_function(
+    ctx: &mut Context,
+    expr_env: &mut TypingEnvironment,
+    subst: Substitution,
+    vars: &[String],
+    expr: &Expr,
+) -> Result<(Substitution, Type, Expr), TypingError> {
+    let arg_renamings = vars
+        .iter()
+        .map(|arg| (arg, fresh_name()))
+        .collect::<Vec<_>>();
+    for (var, fresh) in arg_renamings.clone() {
+        expr_env.insert(
+            var.clone(),
+            (HashSet::new(), vec![], Type::TypeVariable(fresh)),
+        );
+    }
+    let (subst, return_type, expr) = typecheck_expression(ctx, expr_env, subst, expr)?;
+    let fun_type = arg_renamings
+        .into_iter()
+        .map(|(_, arg_type)| arg_type)
+        .fold(return_type, |acc, arg_type| {
+            Type::Function(Box::new(Type::TypeVariable(arg_type)), Box::new(acc))
+        });
+    Ok((subst, fun_type, expr))
+}
+
+/// Discharges the predicates raised since `mark` by a binding of type `t`. Predicates on concrete
+/// types are resolved to instance dictionaries, those on type variables free in `type_env` are
+/// left to an enclosing binding and the rest are handled as `discharge` says. Returns the
+/// predicates abstracted over together with their dictionary parameters.
+fn discharge_predicates(
+    ctx: &mut Context,
+    subst: Substitution,
+    type_env: &TypingEnvironment,
+    mark: usize,
+    t: &Type,
+    discharge: Discharge,
+) -> Result<(Substitution, Vec<(Predicate, String)>), TypingError> {
+    let env_vars = unknowns_te(&sub_type_env(&subst, type_env))
+        .into_iter()
+        .cloned()
+        .collect::<HashSet<_>>();
+    let type_vars = tvars_in(&sub_type(&subst, t))
+        .into_iter()
+        .cloned()
+        .collect::<HashSet<_>>();
+    let mut subst = subst;
+    let mut queue = ctx.pending.split_off(mark);
+    let mut deferred = vec![];
+    let mut abstracted: Vec<(Predicate, String)> = vec![];
+    loop {
+        let mut ambiguous = vec![];
+        while let Some((placeholder, (class, typ))) = queue.pop() {
+            let typ = sub_type(&subst, &typ);
+            let var = match &typ {
+                Type::TypeVariable(var) => var,
+                _ => {
+                    let dictionary = instance_dictionary(ctx, &mut queue, &class, &typ)?;
+                    ctx.dictionaries.insert(placeholder, dictionary);
+                    continue;
+                }
+            };
+            if let Discharge::Given(givens) = discharge {
+                let dictionary = givens.iter().find_map(|((given_class, given_type), dict)| {
+                    match sub_type(&subst, given_type) == typ {
+                        true => ctx.classes.by_superclass(given_class, dict.clone(), &class),
+                        false => None,
+                    }
+                });
+                if let Some(dictionary) = dictionary {
+                    ctx.dictionaries.insert(placeholder, dictionary);
+                    continue;
+                }
+            }
+            if env_vars.contains(var) {
+                deferred.push((placeholder, (class, typ)));
+                continue;
+            }
+            if !type_vars.contains(var) {
+                ambiguous.push((placeholder, (class, typ)));
+                continue;
+            }
+            match discharge {
+                Discharge::Abstract => {
+                    let predicate = (class, typ);
+                    let param = match abstracted.iter().find(|(p, _)| *p == predicate) {
+                        Some((_, param)) => param.clone(),
+                        None => {
+                            let param = format!("dict{}", fresh_name());
+                            abstracted.push((predicate, param.clone()));
+                            param
+                        }
+                    };
+                    ctx.dictionaries.insert(placeholder, Expr::Var(param));
+                }
+                Discharge::Given(_) => return Err(TypingError::CannotDeduce(class, typ)),
+                Discharge::Default => ambiguous.push((placeholder, (class, typ))),
+            }
+        }
+        if ambiguous.is_empty() {
+            break;
+        }
+        subst = default_ambiguous(ctx, subst, &ambiguous)?;
+        queue = ambiguous;
+    }
+    ctx.pending.extend(deferred);
+    Ok((subst, abstracted))
+}
+
+/// Builds the dictionary for `class` at a type with a type constructor at its head, queueing the
+/// predicates the instance context asks for.
+fn instance_dictionary(
+    ctx: &Context,
+    queue: &mut Vec<(String, Predicate)>,
+    class: &str,
+    typ: &Type,
+) -> Result<Expr, TypingError> {
+    let no_instance = || TypingError::NoInstance(class.to_string(), typ.clone());
+    let (head, args) = type_head(typ).ok_or_else(no_instance)?;
+    let instance = ctx.classes.instance(class, &head).ok_or_else(no_instance)?;
+    let mut dictionary = Expr::Var(dictionary_name(class, &head));
+    for (context_class, var) in &instance.context {
+        let index = instance.params.iter().position(|param| param == var);
+        let arg = index.and_then(|i| args.get(i)).ok_or_else(no_instance)?;
+        let placeholder = format!("dict{}", fresh_name());
+        queue.push((placeholder.clone(), (context_class.clone(), arg.clone())));
+        dictionary = Expr::Application(Box::new(dictionary), Box::new(Expr::Var(placeholder)));
+    }
+    Ok(dictionary)
+}
+
+/// Picks the first default type that has an instance for every class constraining an ambiguous
+/// type variable.
+fn default_ambiguous(
+    ctx: &Context,
+    mut subst: Substitution,
+    ambiguous: &[(String, Predicate)],
+) -> Result<Substitution, TypingError> {
+    for (_, (class, typ)) in ambiguous {
+        let var = match typ {
+            Type::TypeVariable(var) if subst.apply(var) == *typ => var,
+            _ => continue,
+        };
+        let classes = ambiguous
+            .iter()
+            .filter(|(_, (_, t))| t == typ)
+            .map(|(_, (class, _))| class)
+            .collect::<Vec<_>>();
+        let default = DEFAULT_TYPES.iter().find(|t| {
+            let (head, _) = type_head(t).unwrap();
+            classes
+                .iter()
+                .all(|class| ctx.classes.instance(class, &head).is_some())
+        });
+        match default {
+            Some(default) => subst = subst.extended(var.clone(), default.clone())?,
+            None => return Err(TypingError::AmbiguousType(class.clone(), typ.clone())),
+        }
+    }
+    Ok(subst)
+}
+
+/// Looks up the type scheme of a variable and instantiates it, applying the variable to a
+/// placeholder for every dictionary it needs.
+fn instantiate(
+    ctx: &mut Context,
+    type_env: &TypingEnvironment,
+    var: &String,
+    expr: Expr,
+) -> Result<(Type, Expr), TypingError> {
+    let (scheme_vars, predicates, old_type) = type_env
+        .get(var)
+        .ok_or(TypingError::UnknownIdentifier(var.clone()))?;
+    if let Some((mono_var, uses)) = ctx.recursive.get_mut(var) {
+        if *old_type == Type::TypeVariable(mono_var.clone()) {
+            let placeholder = format!("rec{}", fresh_name());
+            uses.push(placeholder.clone());
+            return Ok((old_type.clone(), Expr::Var(placeholder)));
+        }
+    }
+    let map = scheme_vars
+        .iter()
+        .map(|var| (var.clone(), Type::TypeVariable(fresh_name())))
+        .collect::<HashMap<_, _>>();
+    let phi = Substitution::from(map);
+    let new_type = sub_type(&phi, old_type);
+    info!("Giving {var} :: {old_type} new type {new_type}");
+    let expr = predicates.iter().fold(expr, |acc, (class, t)| {
+        let placeholder = format!("dict{}", fresh_name());
+        ctx.pending
+            .push((placeholder.clone(), (class.clone(), sub_type(&phi, t))));
+        Expr::Application(Box::new(acc), Box::new(Expr::Var(placeholder)))
+    });
+    Ok((new_type, expr))
+}
+
+/// Whether `op` stands for the class method of the same name rather than a primitive
+fn is_method(op: &Op) -> bool {
+    matches!(
+        op,
+        Op::Add
+            | Op::Sub
+            | Op::Mul
+            | Op::Div
+            | Op::Mod
+            | Op::Eq
+            | Op::Neq
+            | Op::Lt
+            | Op::Gt
+            | Op::Le
+            | Op::Ge
+    )
+}
+
+/// The name of the class method an overloaded operator stands for
+fn method_name(op: &Op) -> String {
+    match op {
+        Op::Mod => "mod".to_string(),
+        op => op.to_string(),
     }
 }
 
 fn typecheck_expression(
+    ctx: &mut Context,
     type_env: &mut TypingEnvironment,
+    subst: Substitution,
     expr: &Expr,
-) -> Result<(Substitution, Type), TypingError> {
+) -> Result<(Substitution, Type, Expr), TypingError> {
     info!("Typechecking expr {}", expr);
-    info!("With Type Env: {:?}", type_env);
     match expr {
         Expr::Var(x) => {
-            let (scheme_vars, old_type) = type_env
-                .get(x)
-                .ok_or(TypingError::UnknownIdentifier(x.clone()))?;
-            let map = scheme_vars
-                .into_iter()
-                .map(|var| (var.clone(), Type::TypeVariable(fresh_name())))
-                .collect::<HashMap<_, _>>();
-            let phi = Substitution::from(map);
-            let new_type = sub_type(&phi, old_type);
-
-            info!("Giving {x} :: {old_type} new type {new_type}");
-            Ok((Substitution::id_subst(), new_type))
+            let (t, e) = instantiate(ctx, type_env, x, expr.clone())?;
+            Ok((subst, t, e))
+        }
+        Expr::Constructor(x) => {
+            let (t, e) = instantiate(ctx, type_env, x, expr.clone())?;
+            Ok((subst, t, e))
         }
         Expr::Application(f, e) => {
-            let (phi, type_f) = typecheck_expression(type_env, f)?;
-            let mut new_env = sub_type_env(&phi, &type_env);
-            let (psi, type_e) = typecheck_expression(&mut new_env, e)?;
-            let subst = subst_combine(psi, phi);
-            let tv_name = fresh_name();
-            let t = Type::TypeVariable(tv_name.clone());
+            let (subst, type_f, f) = typecheck_expression(ctx, type_env, subst, f)?;
+            let (subst, type_e, e) = typecheck_expression(ctx, type_env, subst, e)?;
+            let t = Type::TypeVariable(fresh_name());
             let subst = unify(
                 subst,
                 &type_f,
-                &Type::Function(Box::new(type_e), Box::new(Type::TypeVariable(tv_name))),
+                &Type::Function(Box::new(type_e), Box::new(t.clone())),
             )?;
-            Ok((subst, t))
+            Ok((subst, t, Expr::Application(Box::new(f), Box::new(e))))
         }
         Expr::If(cond, then_branch, else_branch) => {
-            let (phi, type_cond) = typecheck_expression(type_env, cond)?;
-            let (subst_then, type_then) = typecheck_expression(type_env, then_branch)?;
-            let (subst_else, type_else) = typecheck_expression(type_env, else_branch)?;
-            let cond_subst = unify(phi, &type_cond, &Type::Bool)?;
-            let subst_branches = subst_combine(subst_then, subst_else);
-            let subst = subst_combine(cond_subst, subst_branches);
+            let (subst, type_cond, cond) = typecheck_expression(ctx, type_env, subst, cond)?;
+            let subst = unify(subst, &type_cond, &Type::Bool)?;
+            let (subst, type_then, then_branch) =
+                typecheck_expression(ctx, type_env, subst, then_branch)?;
+            let (subst, type_else, else_branch) =
+                typecheck_expression(ctx, type_env, subst, else_branch)?;
             let subst = unify(subst, &type_then, &type_else)?;
-            Ok((subst, type_then))
+            let e = Expr::If(Box::new(cond), Box::new(then_branch), Box::new(else_branch));
+            Ok((subst, type_then, e))
         }
         Expr::Lambda(arg, expr) => {
             let mut type_env = type_env.clone();
-            let fresh = fresh_name();
-            type_env.insert(
-                arg.clone(),
-                (HashSet::new(), Type::TypeVariable(fresh.clone())),
-            );
-            let (subst, ret_type) = typecheck_expression(&mut type_env, expr)?;
-            Ok((
-                subst,
-                Type::Function(Box::new(Type::TypeVariable(fresh)), Box::new(ret_type)),
-            ))
+            let fresh = Type::TypeVariable(fresh_name());
+            type_env.insert(arg.clone(), (HashSet::new(), vec![], fresh.clone()));
+            let (subst, ret_type, expr) = typecheck_expression(ctx, &mut type_env, subst, expr)?;
+            let t = Type::Function(Box::new(fresh), Box::new(ret_type));
+            Ok((subst, t, Expr::Lambda(arg.clone(), Box::new(expr))))
         }
-        Expr::Let(var, expr1, expr2) => {
+        Expr::Let(decls, expr) => {
             let mut let_env = type_env.clone();
-            let (subst1, var_type) = typecheck_expression(&mut let_env, &expr1)?;
-            info!("Resulting in {subst1:?}");
-            let mut let_env = sub_type_env(&subst1, &let_env);
-            info!("For type env {let_env:?}");
-            // add decls
-            let scvs = scvs_given_te(&var_type, &let_env);
-            let mapping = Substitution::from(scvs.into_iter().map(|scv| (scv, Type::TypeVariable(fresh_name()))).collect());
-            let new_type = sub_type(&mapping, &var_type);
-            let scheme_vars = mapping.range();
-            info!("Finding scheme vars {:?}", scheme_vars);
-            let _ = let_env.insert(var.clone(), (scheme_vars, new_type));
-            // end
-            let (subst2, return_type) = typecheck_expression(&mut let_env, &expr2)?;
-            Ok((subst_combine(subst2, subst1), return_type))
+            let (subst, decls) = typecheck_bindings(ctx, &mut let_env, subst, decls)?;
+            let (subst, return_type, expr) = typecheck_expression(ctx, &mut let_env, subst, expr)?;
+            Ok((subst, return_type, Expr::Let(decls, Box::new(expr))))
         }
         Expr::Case(case_expr, cases) => {
-            let mut type_env = type_env.clone();
-            let (expr_subst, case_expr_type) = typecheck_expression(&mut type_env, case_expr)?;
-            let mut subst = expr_subst;
-            // Placeholder there cant be an empty case would be stupid
-            let mut return_type = None;
-            for (pattern, case_body) in cases {
+            let (mut subst, case_expr_type, case_expr) =
+                typecheck_expression(ctx, type_env, subst, case_expr)?;
+            let return_type = Type::TypeVariable(fresh_name());
+            let mut elaborated = vec![];
+            for (pattern, rhs) in cases {
+                let mut case_env = type_env.clone();
                 let (pattern_subst, pattern_type) =
-                    typecheck_pattern(&mut type_env, subst, pattern)?;
+                    typecheck_pattern(ctx, &mut case_env, subst, pattern)?;
                 subst = unify(pattern_subst, &case_expr_type, &pattern_type)?;
-                let (case_subst, case_body_type) = typecheck_expression(&mut type_env, case_body)?;
-                subst = subst_combine(case_subst, subst);
-                if let Some(body_type) = return_type {
-                    subst = unify(subst, &body_type, &case_body_type)?;
-                }
-                return_type = Some(case_body_type);
+                let (phi, rhs) = typecheck_rhs(ctx, &mut case_env, subst, rhs, &return_type)?;
+                subst = phi;
+                elaborated.push((pattern.clone(), rhs));
             }
-            Ok((subst, return_type.unwrap()))
+            Ok((
+                subst,
+                return_type,
+                Expr::Case(Box::new(case_expr), elaborated),
+            ))
         }
         Expr::BinOp(left, op, right) => {
-            let (left_subst, left_type) = typecheck_expression(type_env, left)?;
-            let (right_subst, right_type) = typecheck_expression(type_env, right)?;
-            let subst = subst_combine(left_subst, right_subst);
-            match op {
-                Op::Add | Op::Sub | Op::Mul | Op::Mod | Op::Div => {
-                    let subst = unify(subst, &left_type, &Type::Int)?;
-                    let subst = unify(subst, &right_type, &Type::Int)?;
-                    Ok((subst, Type::Int))
-                }
-                Op::Eq | Op::Neq => {
-                    let subst = unify(subst, &left_type, &right_type)?;
-                    Ok((subst, Type::Bool))
-                },
-                Op::Lt | Op::Gt | Op::Le | Op::Ge => {
-                    let subst = unify(subst, &left_type, &Type::Int)?;
-                    let subst = unify(subst, &left_type, &right_type)?;
-                    Ok((subst, Type::Bool))
-                }
+            if is_method(op) {
+                // Overloaded operators are calls of the class methods of the same name
+                let method = Box::new(Expr::Var(method_name(op)));
+                let call = Expr::Application(
+                    Box::new(Expr::Application(method, left.clone())),
+                    right.clone(),
+                );
+                return typecheck_expression(ctx, type_env, subst, &call);
+            }
+            let (subst, left_type, left) = typecheck_expression(ctx, type_env, subst, left)?;
+            let (subst, right_type, right) = typecheck_expression(ctx, type_env, subst, right)?;
+            let (subst, t) = match op {
                 Op::And | Op::Or => {
                     let subst = unify(subst, &left_type, &Type::Bool)?;
                     let subst = unify(subst, &left_type, &right_type)?;
-                    Ok((subst, Type::Bool))
+                    (subst, Type::Bool)
                 }
                 Op::Append => {
                     let subst = unify(subst, &left_type, &right_type)?;
-                    let subst = unify(subst, &left_type, &right_type)?;
-                    Ok((subst, left_type))
+                    (subst, left_type)
                 }
                 Op::Cons => {
                     let list_type = Type::List(Box::new(left_type));
                     let subst = unify(subst, &list_type, &right_type)?;
-                    Ok((subst, list_type))
+                    (subst, list_type)
+                }
+                _ => unreachable!(),
+            };
+            Ok((
+                subst,
+                t,
+                Expr::BinOp(Box::new(left), op.clone(), Box::new(right)),
+            ))
+        }
+        Expr::Operator(op) => {
+            if is_method(op) {
+                let method = Expr::Var(method_name(op));
+                return typecheck_expression(ctx, type_env, subst, &method);
+            }
+            let binary = |t1: Type, t2: Type, t3: Type| {
+                Type::Function(
+                    Box::new(t1),
+                    Box::new(Type::Function(Box::new(t2), Box::new(t3))),
+                )
+            };
+            let t = match op {
+                Op::And | Op::Or => binary(Type::Bool, Type::Bool, Type::Bool),
+                Op::Append => {
+                    let list = Type::List(Box::new(Type::TypeVariable(fresh_name())));
+                    binary(list.clone(), list.clone(), list)
+                }
+                Op::Cons => {
+                    let a = Type::TypeVariable(fresh_name());
+                    let list = Type::List(Box::new(a.clone()));
+                    binary(a, list.clone(), list)
+                }
+                _ => unreachable!(),
+            };
+            Ok((subst, t, Expr::Operator(op.clone())))
+        }
+        Expr::Infix(_, _) | Expr::Negate(_) => {
+            unreachable!("Infix expressions are resolved by the parser")
+        }
+        Expr::Annotated(e, t) => {
+            let t = resolve_type(&ctx.kinds, &ctx.aliases, t)?;
+            let scheme = (scvs_in_type_signature(&t), vec![], t);
+            // The expression must have the annotated type for any types of its type variables
+            let rigid = scheme
+                .0
+                .iter()
+                .map(|var| (var.clone(), Type::TypeVariable(rigid_name(var))))
+                .collect::<HashMap<_, _>>();
+            let (expected, _, _) = instantiate_signature(&scheme, &rigid);
+            let (subst, e) = check_binding(ctx, type_env, subst, &[], e, &expected, &[])?;
+            let env_vars = unknowns_te(&sub_type_env(&subst, type_env))
+                .into_iter()
+                .cloned()
+                .collect::<HashSet<_>>();
+            for (var, rigid) in &rigid {
+                if matches!(rigid, Type::TypeVariable(name) if env_vars.contains(name)) {
+                    return Err(TypingError::EscapedTypeVariable(var.clone()));
                 }
             }
+            // Its uses only see the annotated type, so the annotation itself is erased
+            let (t, _, _) = instantiate_signature(&scheme, &HashMap::new());
+            Ok((subst, t, e))
         }
         Expr::Tuple(exprs) => {
-            let mut env = type_env.clone();
             let mut types = vec![];
-            let mut subst = Substitution::id_subst();
+            let mut elaborated = vec![];
+            let mut subst = subst;
             for expr in exprs {
-                let (expr_subst, typ) = typecheck_expression(&mut env, expr)?;
-                env = sub_type_env(&expr_subst, &env);
-                subst = subst_combine(expr_subst, subst);
+                let (phi, typ, expr) = typecheck_expression(ctx, type_env, subst, expr)?;
+                subst = phi;
                 types.push(typ);
+                elaborated.push(expr);
             }
-            let tuple_type = Type::Tuple(types.iter().map(|t| sub_type(&subst, &t)).collect());
-            Ok((subst, tuple_type))
+            Ok((subst, Type::Tuple(types), Expr::Tuple(elaborated)))
         }
         Expr::List(es) => match es {
             List::Some(first, tail) => {
-                let (subst_first, type_first) = typecheck_expression(type_env, first)?;
-                let (subst_tail, type_tail) =
-                    typecheck_expression(type_env, &Expr::List(*tail.clone()))?;
-                let subst = subst_combine(subst_first, subst_tail);
-                let subst = unify(subst, &Type::List(Box::new(type_first.clone())), &type_tail)?;
-                Ok((subst, Type::List(Box::new(type_first))))
+                let (subst, type_first, first) = typecheck_expression(ctx, type_env, subst, first)?;
+                let (subst, type_tail, tail) =
+                    typecheck_expression(ctx, type_env, subst, &Expr::List(*tail.clone()))?;
+                let list_type = Type::List(Box::new(type_first));
+                let subst = unify(subst, &list_type, &type_tail)?;
+                let tail = match tail {
+                    Expr::List(tail) => tail,
+                    _ => unreachable!(),
+                };
+                Ok((
+                    subst,
+                    list_type,
+                    Expr::List(List::Some(Box::new(first), Box::new(tail))),
+                ))
             }
             List::Empty => Ok((
-                Substitution::id_subst(),
+                subst,
                 Type::List(Box::new(Type::TypeVariable(fresh_name()))),
+                Expr::List(List::Empty),
             )),
         },
-        Expr::Range(from, step, to) => {
-            let (subst_from, type_from) = typecheck_expression(type_env, from)?;
-            let subst_from = unify(subst_from, &type_from, &Type::Int)?;
-            let (subst_step, type_step) = typecheck_expression(type_env, step)?;
-            let subst_step = unify(subst_step, &type_step, &Type::Int)?;
-            let subst = subst_combine(subst_step, subst_from);
-            let subst = match to {
-                Some(to) => {
-                    let (subst_to, type_to) = typecheck_expression(type_env, to)?;
-                    let subst_to = unify(subst_to, &type_to, &Type::Int)?;
-                    subst_combine(subst_to, subst)
-                }
-                None => subst,
+        Expr::Range(from, then, to) => {
+            let (subst, t, from) = typecheck_expression(ctx, type_env, subst, from)?;
+            let (subst, then) = typecheck_bound(ctx, type_env, subst, then, &t)?;
+            let (subst, to) = typecheck_bound(ctx, type_env, subst, to, &t)?;
+            // Sequences are built by the evaluator itself, which only needs the elements to be
+            // enumerable, so the dictionary is never used
+            let placeholder = format!("dict{}", fresh_name());
+            ctx.pending
+                .push((placeholder, ("Enum".to_string(), t.clone())));
+            let e = Expr::Range(Box::new(from), then, to);
+            Ok((subst, Type::List(Box::new(t)), e))
+        }
+        Expr::Literal(Literal::Int(n)) => {
+            typecheck_numeric(ctx, type_env, subst, Literal::Integer(BigInt::from(*n)))
+        }
+        Expr::Literal(n @ (Literal::Integer(_) | Literal::Double(_))) => {
+            typecheck_numeric(ctx, type_env, subst, n.clone())
+        }
+        Expr::Literal(literal) => {
+            let t = match literal {
+                Literal::Int(_) | Literal::Integer(_) | Literal::Double(_) => unreachable!(),
+                Literal::Bool(_) => Type::Bool,
+                Literal::Char(_) => Type::Char,
+                Literal::String(_) => Type::List(Box::new(Type::Char)),
             };
-            Ok((subst, Type::List(Box::new(Type::Int))))
+            Ok((subst, t, expr.clone()))
         }
-        Expr::Literal(Literal::Int(_)) => Ok((Substitution::id_subst(), Type::Int)),
-        Expr::Literal(Literal::Bool(_)) => Ok((Substitution::id_subst(), Type::Bool)),
-        Expr::Literal(Literal::Char(_)) => Ok((Substitution::id_subst(), Type::Char)),
-        Expr::Literal(Literal::String(_)) => Ok((Substitution::id_subst(), Type::String)),
     }
 }
 
+/// Typechecks a numeric literal, which stands for `fromInteger n` (or `fromDouble x` for a
+/// fractional literal) at whatever type of the `Num` (or `Fractional`) class it is used at
+fn typecheck_numeric(
+    ctx: &mut Context,
+    type_env: &TypingEnvironment,
+    subst: Substitution,
+    literal: Literal,
+) -> Result<(Substitution, Type, Expr), TypingError> {
+    let (method, literal_type) = match literal {
+        Literal::Double(_) => ("fromDouble".to_string(), Type::Double),
+        _ => ("fromInteger".to_string(), Type::Integer),
+    };
+    let (t, f) = instantiate(ctx, type_env, &method, Expr::Var(method.clone()))?;
+    let result = Type::TypeVariable(fresh_name());
+    let expected = Type::Function(Box::new(literal_type), Box::new(result.clone()));
+    let subst = unify(subst, &t, &expected)?;
+    let e = Expr::Application(Box::new(f), Box::new(Expr::Literal(literal)));
+    Ok((subst, result, e))
+}
+
+/// Typechecks an optional bound of an arithmetic sequence with elements of type `t`
+fn typecheck_bound(
+    ctx: &mut Context,
+    type_env: &mut TypingEnvironment,
+    subst: Substitution,
+    bound: &Option<Box<Expr>>,
+    t: &Type,
+) -> Result<(Substitution, Option<Box<Expr>>), TypingError> {
+    let Some(bound) = bound else {
+        return Ok((subst, None));
+    };
+    let (subst, bound_type, bound) = typecheck_expression(ctx, type_env, subst, bound)?;
+    let subst = unify(subst, t, &bound_type)?;
+    Ok((subst, Some(Box::new(bound))))
+}
+
+/// Typechecks the right-hand side of a case alternative, every expression of which has type `t`
+fn typecheck_rhs(
+    ctx: &mut Context,
+    type_env: &mut TypingEnvironment,
+    subst: Substitution,
+    rhs: &Rhs,
+    t: &Type,
+) -> Result<(Substitution, Rhs), TypingError> {
+    match rhs {
+        Rhs::Expr(e) => {
+            let (subst, e_type, e) = typecheck_expression(ctx, type_env, subst, e)?;
+            Ok((unify(subst, t, &e_type)?, Rhs::Expr(e)))
+        }
+        Rhs::Guarded(alternatives) => {
+            let mut subst = subst;
+            let mut elaborated = vec![];
+            for (guards, e) in alternatives {
+                // Pattern guards bind variables in the guards after them and the expression
+                let mut guard_env = type_env.clone();
+                let mut checked = vec![];
+                for guard in guards {
+                    match guard {
+                        Guard::Bool(cond) => {
+                            let (phi, cond_type, cond) =
+                                typecheck_expression(ctx, &mut guard_env, subst, cond)?;
+                            subst = unify(phi, &cond_type, &Type::Bool)?;
+                            checked.push(Guard::Bool(cond));
+                        }
+                        Guard::Pattern(pattern, e) => {
+                            let (phi, e_type, e) =
+                                typecheck_expression(ctx, &mut guard_env, subst, e)?;
+                            let (phi, pattern_type) =
+                                typecheck_pattern(ctx, &mut guard_env, phi, pattern)?;
+                            subst = unify(phi, &e_type, &pattern_type)?;
+                            checked.push(Guard::Pattern(pattern.clone(), e));
+                        }
+                    }
+                }
+                let (phi, e_type, e) = typecheck_expression(ctx, &mut guard_env, subst, e)?;
+                subst = unify(phi, t, &e_type)?;
+                elaborated.push((checked, e));
+            }
+            Ok((subst, Rhs::Guarded(elaborated)))
+        }
+        Rhs::Where(decls, rhs) => {
+            let mut where_env = type_env.clone();
+            let (subst, decls) = typecheck_bindings(ctx, &mut where_env, subst, decls)?;
+            let (subst, rhs) = typecheck_rhs(ctx, &mut where_env, subst, rhs, t)?;
+            Ok((subst, Rhs::Where(decls, Box::new(rhs))))
+        }
+    }
+}
+
+/// The type of a numeric literal pattern, which may be any type of `class`. The evaluator compares
+/// numbers itself, so the dictionary is never used.
+fn numeric_pattern(ctx: &mut Context, class: &str) -> Type {
+    let t = Type::TypeVariable(fresh_name());
+    let placeholder = format!("dict{}", fresh_name());
+    ctx.pending
+        .push((placeholder, (class.to_string(), t.clone())));
+    t
+}
+
 fn typecheck_pattern(
+    ctx: &mut Context,
     type_env: &mut TypingEnvironment,
     subst: Substitution,
     pattern: &Pattern,
 ) -> Result<(Substitution, Type), TypingError> {
     info!("Type checking pattern {}", pattern);
     match pattern {
-        Pattern::Literal(Literal::Int(_)) => Ok((Substitution::id_subst(), Type::Int)),
-        Pattern::Literal(Literal::Bool(_)) => Ok((Substitution::id_subst(), Type::Bool)),
-        Pattern::Literal(Literal::Char(_)) => Ok((Substitution::id_subst(), Type::Char)),
-        Pattern::Literal(Literal::String(_)) => Ok((Substitution::id_subst(), Type::String)),
+        Pattern::Literal(Literal::Int(_) | Literal::Integer(_)) => {
+            Ok((subst, numeric_pattern(ctx, "Num")))
+        }
+        Pattern::Literal(Literal::Double(_)) => Ok((subst, numeric_pattern(ctx, "Fractional"))),
+        Pattern::Literal(Literal::Bool(_)) => Ok((subst, Type::Bool)),
+        Pattern::Literal(Literal::Char(_)) => Ok((subst, Type::Char)),
+        Pattern::Literal(Literal::String(_)) => Ok((subst, Type::List(Box::new(Type::Char)))),
         Pattern::Var(var_name) => {
             let fresh = fresh_name();
             let type_variable = Type::TypeVariable(fresh.clone());
-            type_env.insert(var_name.clone(), (HashSet::new(), type_variable.clone()));
-            Ok((Substitution::id_subst(), type_variable))
+            type_env.insert(
+                var_name.clone(),
+                (HashSet::new(), vec![], type_variable.clone()),
+            );
+            Ok((subst, type_variable))
+        }
+        Pattern::As(var_name, p) => {
+            let (subst, pattern_type) = typecheck_pattern(ctx, type_env, subst, p)?;
+            type_env.insert(
+                var_name.clone(),
+                (HashSet::new(), vec![], pattern_type.clone()),
+            );
+            Ok((subst, pattern_type))
         }
+        Pattern::Bang(p) | Pattern::Lazy(p) => typecheck_pattern(ctx, type_env, subst, p),
         Pattern::List(first, tail) => {
-            let (subst, first_type) = typecheck_pattern(type_env, subst, first)?;
-            let (subst, tail_type) = typecheck_pattern(type_env, subst, tail)?;
+            let (subst, first_type) = typecheck_pattern(ctx, type_env, subst, first)?;
+            let (subst, tail_type) = typecheck_pattern(ctx, type_env, subst, tail)?;
             info!("Type checking list pattern ({}:{})", first_type, tail_type);
             let list_type = Type::List(Box::new(first_type.clone()));
             let subst = unify(subst, &list_type, &tail_type)?;
@@ -303,7 +1379,7 @@ fn typecheck_pattern(
             let mut types = vec![];
             let mut current_subst = subst;
             for pattern in ps {
-                let (phi, pattern_type) = typecheck_pattern(type_env, current_subst, pattern)?;
+                let (phi, pattern_type) = typecheck_pattern(ctx, type_env, current_subst, pattern)?;
                 current_subst = phi;
                 types.push(pattern_type);
             }
@@ -313,17 +1389,45 @@ fn typecheck_pattern(
             let mut types = vec![];
             let mut current_subst = subst;
             for pattern in ps {
-                let (phi, pattern_type) = typecheck_pattern(type_env, current_subst, pattern)?;
+                let (phi, pattern_type) = typecheck_pattern(ctx, type_env, current_subst, pattern)?;
                 current_subst = phi;
                 types.push(pattern_type);
             }
             Ok((current_subst, Type::Tuple(types)))
         }
+        Pattern::Constructor(name, ps) => {
+            let (scheme_vars, _, constructor_type) = type_env
+                .get(name)
+                .ok_or(TypingError::UnknownIdentifier(name.clone()))?;
+            let map = scheme_vars
+                .iter()
+                .map(|var| (var.clone(), Type::TypeVariable(fresh_name())))
+                .collect::<HashMap<_, _>>();
+            let mut constructor_type = sub_type(&Substitution::from(map), constructor_type);
+            let mut field_types = vec![];
+            while let Type::Function(field, result) = constructor_type {
+                field_types.push(*field);
+                constructor_type = *result;
+            }
+            if field_types.len() != ps.len() {
+                return Err(TypingError::ConstructorArity(
+                    name.clone(),
+                    field_types.len(),
+                    ps.len(),
+                ));
+            }
+            let mut current_subst = subst;
+            for (pattern, field_type) in zip(ps, field_types) {
+                let (phi, pattern_type) = typecheck_pattern(ctx, type_env, current_subst, pattern)?;
+                current_subst = unify(phi, &field_type, &pattern_type)?;
+            }
+            Ok((current_subst, constructor_type))
+        }
         Pattern::EmptyList => Ok((
-            Substitution::id_subst(),
+            subst,
             Type::List(Box::new(Type::TypeVariable(fresh_name()))),
         )),
-        Pattern::Wildcard => Ok((Substitution::id_subst(), Type::TypeVariable(fresh_name()))),
+        Pattern::Wildcard => Ok((subst, Type::TypeVariable(fresh_name()))),
     }
 }
 
@@ -335,6 +1439,13 @@ fn unify(phi: Substitution, t1: &Type, t2: &Type) -> Result<Substitution, Typing
             let phitvn = phi.apply(&tv_name);
             info!("Unification: Translated {} to {}", tv_name, phitvn);
             if phitvn == Type::TypeVariable(tv_name.clone()) {
+                if is_rigid(tv_name) && phit != phitvn {
+                    // A rigid type variable can only be the type a flexible one stands for
+                    return match phit {
+                        Type::TypeVariable(var) if !is_rigid(&var) => phi.extended(var, phitvn),
+                        _ => Err(TypingError::CannotUnify(phitvn, phit)),
+                    };
+                }
                 return phi.extended(tv_name.clone(), phit);
             }
             unify(phi, &phitvn, &phit)
@@ -350,10 +1461,18 @@ fn unify(phi: Substitution, t1: &Type, t2: &Type) -> Result<Substitution, Typing
             })
         }
         (Type::Int, Type::Int)
+        | (Type::Integer, Type::Integer)
+        | (Type::Double, Type::Double)
         | (Type::Bool, Type::Bool)
         | (Type::Char, Type::Char)
         | (Type::String, Type::String) => Ok(phi),
         (Type::List(type1), Type::List(type2)) => unify(phi, type1, type2),
+        (Type::IO(type1), Type::IO(type2)) => unify(phi, type1, type2),
+        (Type::Constructor(name1, args1), Type::Constructor(name2, args2))
+            if name1 == name2 && args1.len() == args2.len() =>
+        {
+            zip(args1, args2).try_fold(phi, |acc_phi, (t1, t2)| unify(acc_phi, t1, t2))
+        }
         (x, y) => Err(TypingError::CannotUnify(x.clone(), y.clone())),
     }
 }
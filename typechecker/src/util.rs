@@ -1,7 +1,8 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use ast::ast::Type;
+use ast::ast::{Decl, Expr, Guard, List, Literal, Rhs, Type};
 
+use crate::class::dictionary_name;
 use crate::subst::Substitution;
 use crate::typecheck::TypeScheme;
 use crate::typecheck::TypingEnvironment;
@@ -16,6 +17,18 @@ pub fn fresh_name() -> String {
     res
 }
 
+/// A rigid type variable standing for the type variable `var` of an annotation. It unifies with
+/// no type other than itself or a flexible type variable.
+pub fn rigid_name(var: &str) -> String {
+    format!("{}{}", var, fresh_name())
+}
+
+/// Whether a type variable is rigid. Flexible type variables are either fresh or written in a
+/// signature, so only rigid ones both start with a letter and carry a fresh suffix.
+pub fn is_rigid(var: &str) -> bool {
+    !var.starts_with('<') && var.ends_with('>')
+}
+
 pub fn scvs_in_type_signature(t: &Type) -> HashSet<String> {
     tvars_in(t)
         .into_iter()
@@ -34,7 +47,7 @@ pub fn scvs_given_te(t: &Type, type_env: &TypingEnvironment) -> HashSet<String>
         .collect()
 }
 
-fn unknowns_te(type_env: &TypingEnvironment) -> HashSet<&String> {
+pub fn unknowns_te(type_env: &TypingEnvironment) -> HashSet<&String> {
     type_env
         .into_iter()
         .flat_map(|(_, scheme)| unknowns_scheme(scheme))
@@ -42,7 +55,7 @@ fn unknowns_te(type_env: &TypingEnvironment) -> HashSet<&String> {
 }
 
 fn unknowns_scheme(scheme: &TypeScheme) -> HashSet<&String> {
-    let (scvs, typ) = scheme;
+    let (scvs, _, typ) = scheme;
     let tvars = tvars_in(typ);
     tvars
         .into_iter()
@@ -60,8 +73,9 @@ pub fn tvars_in(t: &Type) -> Vec<&String> {
             v1
         }
         Type::Tuple(ts) => ts.into_iter().flat_map(tvars_in).collect(),
-        Type::List(t) => tvars_in(t),
-        Type::Int | Type::Bool | Type::Char | Type::String => vec![],
+        Type::List(t) | Type::IO(t) => tvars_in(t),
+        Type::Constructor(_, ts) => ts.iter().flat_map(tvars_in).collect(),
+        Type::Int | Type::Integer | Type::Double | Type::Bool | Type::Char | Type::String => vec![],
     }
 }
 
@@ -73,9 +87,13 @@ pub fn sub_type_env(subst: &Substitution, type_env: &TypingEnvironment) -> Typin
 }
 
 pub fn sub_scheme(subst: &Substitution, scheme_type: &TypeScheme) -> TypeScheme {
-    let (scheme_vars, t) = scheme_type;
-    let new_scheme = sub_type(&subst.exclude(&scheme_vars), &t);
-    (scheme_vars.clone(), new_scheme)
+    let (scheme_vars, predicates, t) = scheme_type;
+    let subst = subst.exclude(scheme_vars);
+    let predicates = predicates
+        .iter()
+        .map(|(class, t)| (class.clone(), sub_type(&subst, t)))
+        .collect();
+    (scheme_vars.clone(), predicates, sub_type(&subst, t))
 }
 
 pub fn sub_type(subst: &Substitution, t: &Type) -> Type {
@@ -89,6 +107,8 @@ pub fn sub_type(subst: &Substitution, t: &Type) -> Type {
                     sub_type(subst, &Type::TypeVariable(new_tv_name))
                 },
                 Type::Int => Type::Int,
+                Type::Integer => Type::Integer,
+                Type::Double => Type::Double,
                 Type::Bool => Type::Bool,
                 Type::Char => Type::Char,
                 Type::String => Type::String,
@@ -104,9 +124,236 @@ pub fn sub_type(subst: &Substitution, t: &Type) -> Type {
                 .collect(),
         ),
         Type::Int => Type::Int,
+        Type::Integer => Type::Integer,
+        Type::Double => Type::Double,
         Type::Bool => Type::Bool,
         Type::Char => Type::Char,
         Type::String => Type::String,
         Type::List(t) => Type::List(Box::new(sub_type(subst, t))),
+        Type::IO(t) => Type::IO(Box::new(sub_type(subst, t))),
+        Type::Constructor(name, ts) => Type::Constructor(
+            name.clone(),
+            ts.iter().map(|t| sub_type(subst, t)).collect(),
+        ),
+    }
+}
+
+/// Replaces the variables standing in for dictionaries by the dictionaries found for them.
+pub fn replace_vars(expr: Expr, replacements: &HashMap<String, Expr>) -> Expr {
+    let replace = |e: Box<Expr>| Box::new(replace_vars(*e, replacements));
+    match expr {
+        Expr::Var(name) => match replacements.get(&name) {
+            Some(e) => replace_vars(e.clone(), replacements),
+            None => Expr::Var(name),
+        },
+        Expr::Application(f, e) => fold_literal(Expr::Application(replace(f), replace(e))),
+        Expr::If(a, b, c) => Expr::If(replace(a), replace(b), replace(c)),
+        Expr::Let(decls, e) => Expr::Let(
+            decls
+                .into_iter()
+                .map(|decl| replace_decl(decl, replacements))
+                .collect(),
+            replace(e),
+        ),
+        Expr::Lambda(arg, e) => Expr::Lambda(arg, replace(e)),
+        Expr::Case(e, cases) => Expr::Case(
+            replace(e),
+            cases
+                .into_iter()
+                .map(|(p, rhs)| (p, replace_rhs(rhs, replacements)))
+                .collect(),
+        ),
+        Expr::BinOp(l, op, r) => Expr::BinOp(replace(l), op, replace(r)),
+        Expr::Tuple(es) => Expr::Tuple(
+            es.into_iter()
+                .map(|e| replace_vars(e, replacements))
+                .collect(),
+        ),
+        Expr::List(ls) => Expr::List(replace_list(ls, replacements)),
+        Expr::Range(from, then, to) => Expr::Range(replace(from), then.map(replace), to.map(replace)),
+        e @ (Expr::Constructor(_) | Expr::Literal(_) | Expr::Operator(_)) => e,
+        Expr::Infix(_, _) | Expr::Negate(_) => {
+            unreachable!("Infix expressions are resolved by the parser")
+        }
+        Expr::Annotated(_, _) => unreachable!("Annotations are erased by the typechecker"),
+    }
+}
+
+/// Numeric literals used at Int, Integer or Double need no call of `fromInteger` or `fromDouble`
+/// once their dictionary is known
+fn fold_literal(e: Expr) -> Expr {
+    let Expr::Application(f, arg) = &e else {
+        return e;
+    };
+    let (Expr::Application(method, dict), Expr::Literal(literal)) = (&**f, &**arg) else {
+        return e;
+    };
+    let (Expr::Var(method), Expr::Var(dict)) = (&**method, &**dict) else {
+        return e;
+    };
+    match (method.as_str(), literal) {
+        ("fromDouble", Literal::Double(_)) if *dict == dictionary_name("Fractional", "Double") => {
+            Expr::Literal(literal.clone())
+        }
+        ("fromInteger", Literal::Integer(_)) if *dict == dictionary_name("Num", "Integer") => {
+            Expr::Literal(literal.clone())
+        }
+        ("fromInteger", Literal::Integer(n)) if *dict == dictionary_name("Num", "Int") => {
+            match i64::try_from(n) {
+                Ok(n) => Expr::Literal(Literal::Int(n)),
+                Err(_) => e,
+            }
+        }
+        _ => e,
+    }
+}
+
+fn replace_rhs(rhs: Rhs, replacements: &HashMap<String, Expr>) -> Rhs {
+    match rhs {
+        Rhs::Expr(e) => Rhs::Expr(replace_vars(e, replacements)),
+        Rhs::Guarded(alternatives) => Rhs::Guarded(
+            alternatives
+                .into_iter()
+                .map(|(guards, e)| {
+                    let guards = guards
+                        .into_iter()
+                        .map(|guard| match guard {
+                            Guard::Bool(cond) => Guard::Bool(replace_vars(cond, replacements)),
+                            Guard::Pattern(p, e) => Guard::Pattern(p, replace_vars(e, replacements)),
+                        })
+                        .collect();
+                    (guards, replace_vars(e, replacements))
+                })
+                .collect(),
+        ),
+        Rhs::Where(decls, rhs) => Rhs::Where(
+            decls
+                .into_iter()
+                .map(|decl| replace_decl(decl, replacements))
+                .collect(),
+            Box::new(replace_rhs(*rhs, replacements)),
+        ),
+    }
+}
+
+pub fn replace_decl(decl: Decl, replacements: &HashMap<String, Expr>) -> Decl {
+    match decl {
+        Decl::FunDecl(name, args, e) => Decl::FunDecl(name, args, replace_vars(e, replacements)),
+        Decl::SExpr(e) => Decl::SExpr(replace_vars(e, replacements)),
+        decl => decl,
+    }
+}
+
+fn replace_list(ls: List<Expr>, replacements: &HashMap<String, Expr>) -> List<Expr> {
+    match ls {
+        List::Some(e, es) => List::Some(
+            Box::new(replace_vars(*e, replacements)),
+            Box::new(replace_list(*es, replacements)),
+        ),
+        List::Empty => List::Empty,
+    }
+}
+
+/// The variables an expression refers to, including those it binds itself
+pub fn vars_in(expr: &Expr) -> HashSet<&String> {
+    match expr {
+        Expr::Var(name) => HashSet::from([name]),
+        Expr::Application(f, e) => vars_in(f).into_iter().chain(vars_in(e)).collect(),
+        Expr::If(a, b, c) => [a, b, c].into_iter().flat_map(|e| vars_in(e)).collect(),
+        Expr::Let(decls, e) => vars_in_decls(decls).into_iter().chain(vars_in(e)).collect(),
+        Expr::Lambda(_, e) | Expr::Annotated(e, _) => vars_in(e),
+        Expr::Case(e, cases) => cases
+            .iter()
+            .flat_map(|(_, rhs)| vars_in_rhs(rhs))
+            .chain(vars_in(e))
+            .collect(),
+        Expr::BinOp(l, _, r) => vars_in(l).into_iter().chain(vars_in(r)).collect(),
+        Expr::Tuple(es) => es.iter().flat_map(vars_in).collect(),
+        Expr::List(ls) => {
+            let mut vars = HashSet::new();
+            let mut current = ls;
+            while let List::Some(e, es) = current {
+                vars.extend(vars_in(e));
+                current = es;
+            }
+            vars
+        }
+        Expr::Range(from, then, to) => [Some(from), then.as_ref(), to.as_ref()]
+            .into_iter()
+            .flatten()
+            .flat_map(|e| vars_in(e))
+            .collect(),
+        Expr::Constructor(_) | Expr::Literal(_) | Expr::Operator(_) => HashSet::new(),
+        Expr::Infix(_, _) | Expr::Negate(_) => {
+            unreachable!("Infix expressions are resolved by the parser")
+        }
+    }
+}
+
+fn vars_in_rhs(rhs: &Rhs) -> HashSet<&String> {
+    match rhs {
+        Rhs::Expr(e) => vars_in(e),
+        Rhs::Guarded(alternatives) => alternatives
+            .iter()
+            .flat_map(|(guards, e)| {
+                guards
+                    .iter()
+                    .flat_map(|guard| match guard {
+                        Guard::Bool(e) | Guard::Pattern(_, e) => vars_in(e),
+                    })
+                    .chain(vars_in(e))
+            })
+            .collect(),
+        Rhs::Where(decls, rhs) => vars_in_decls(decls)
+            .into_iter()
+            .chain(vars_in_rhs(rhs))
+            .collect(),
+    }
+}
+
+fn vars_in_decls(decls: &[Decl]) -> HashSet<&String> {
+    decls
+        .iter()
+        .flat_map(|decl| match decl {
+            Decl::FunDecl(_, _, e) => vars_in(e),
+            _ => HashSet::new(),
+        })
+        .collect()
+}
+
+/// Splits a graph, given by the nodes each node uses, into its strongly connected components.
+/// Every component comes after the components it uses.
+pub fn dependency_order(uses: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let reachable = (0..uses.len())
+        .map(|node| {
+            let mut seen = HashSet::new();
+            let mut stack = uses[node].clone();
+            while let Some(next) = stack.pop() {
+                if seen.insert(next) {
+                    stack.extend(&uses[next]);
+                }
+            }
+            seen
+        })
+        .collect::<Vec<_>>();
+    let mut done = vec![false; uses.len()];
+    let mut components = vec![];
+    while let Some(node) = (0..uses.len()).find(|&node| {
+        !done[node]
+            && reachable[node]
+                .iter()
+                .all(|&other| done[other] || reachable[other].contains(&node))
+    }) {
+        let component = (0..uses.len())
+            .filter(|&other| {
+                other == node
+                    || (reachable[node].contains(&other) && reachable[other].contains(&node))
+            })
+            .collect::<Vec<_>>();
+        for &member in &component {
+            done[member] = true;
+        }
+        components.push(component);
     }
+    components
 }
@@ -0,0 +1,19 @@
+empty = ([] :: [Char])
+
+ident = (\x -> x) :: a -> a
+
+pair = (ident 1, ident True)
+
+inc = \(x :: Int) (y :: Int) -> x + y
+
+halve = let (d :: Double) = 2 in \x -> x / d
+
+f x = (x :: Int) + 1 :: Int
+
+size = case ([] :: [a]) of
+  [] -> 0
+  _ -> 1
+
+size
+halve 3
+f 2
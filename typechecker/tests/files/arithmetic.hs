@@ -0,0 +1,17 @@
+map f [] = []
+map f (x:xs) = f x : map f xs
+
+sign :: Int -> Int
+sign 0 = 0
+sign (-1) = -1
+sign n = signum n
+
+distance :: Int -> Int -> Int
+distance x y = abs (x - y)
+
+halves :: Int -> (Int, Int)
+halves n = (n `div` 2, n `rem` 2)
+
+square x = x ^ 2
+
+negated = map negate [1, 2, 3]
@@ -0,0 +1,30 @@
+data Maybe' a = Just a | Nothing
+
+data Shape = Circle Int | Rect Int Int
+
+describe s = case s of
+  Circle 0 -> "dot"
+  Circle r | r > 10 -> "big circle"
+           | otherwise -> "circle"
+  Rect w h
+    | w == h -> "square"
+  _ -> "shape"
+
+classify n = case n of { 0 -> "zero"; -1 -> "minus one"; _ -> "other" }
+
+firstTwo xs = case xs of
+  (a:b:rest) -> a + b
+  (x:[]) -> x
+  [] -> 0
+
+sumPair p = case p of (a, b) -> a + b where { unused = 1 }
+
+nested m = case m of
+  Just (x, (Just y)) -> case x of
+    0 -> y
+    _ -> x * y
+  _ -> 0
+
+describe (Circle 0)
+describe (Circle 20)
+describe (Circle 3)
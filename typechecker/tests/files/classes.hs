@@ -0,0 +1,20 @@
+data Color = Red | Green | Blue
+
+class Describe a where { describe :: a -> String ; name :: a -> String ; name x = describe x }
+instance Describe Color where { describe Red = "red" ; describe c = "other" }
+instance Eq Color where { (==) Red Red = True ; (==) Green Green = True ; (==) Blue Blue = True ; (==) x y = False }
+
+elem x [] = False
+elem x (y:ys) = (x == y) || (elem x ys)
+
+member :: Eq a => a -> [a] -> Bool
+member x [] = False
+member x (y:ys) = (x == y) || (member x ys)
+
+maximum :: Ord a => a -> [a] -> a
+maximum m [] = m
+maximum m (x:xs) = if x > m then maximum x xs else maximum m xs
+
+(elem 'c' ['a', 'b'], member Red [Green, Red], elem [1] [[2], [1]], maximum 'a' ['z', 'b'])
+
+(show 3, show 'c', 1 + 2 * 3, name Blue)
@@ -0,0 +1,13 @@
+data Maybe a = Nothing | Just a
+
+evens :: [Int] -> [Int]
+evens xs = [x | x <- xs, x `mod` 2 == 0]
+
+pairs = [(x, y) | x <- [1, 2], y <- [True, False]]
+
+squares = [y | x <- [1..], let y = x * x, y > 3]
+
+justs :: [Maybe a] -> [a]
+justs xs = [x | Just x <- xs]
+
+nested = [[(a, b) | b <- [1..3], a < b] | a <- [1..3]]
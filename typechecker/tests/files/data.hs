@@ -0,0 +1,19 @@
+data Color = Red | Green | Blue
+data Tree a = Leaf | Node (Tree a) a (Tree a)
+data Shape = Circle Int | Rect Int Int
+
+size :: Tree a -> Int
+size Leaf = 0
+size (Node l _ r) = (size l) + 1 + (size r)
+
+insert :: Int -> Tree Int -> Tree Int
+insert x Leaf = Node Leaf x Leaf
+insert x (Node l y r) = if x < y then Node (insert x l) y r else Node l y (insert x r)
+
+area (Circle r) = 3 * r * r
+area (Rect w h) = w * h
+
+favourite :: Color
+favourite = Green
+
+(size (Node Leaf Red Leaf), size (insert 3 Leaf), area (Rect 2 3))
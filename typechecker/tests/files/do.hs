@@ -0,0 +1,16 @@
+greet :: String -> IO ()
+greet name = do
+  putStr "Hello, "
+  putStrLn name
+
+ask :: String -> IO String
+ask question = putStrLn question >> getLine
+
+main :: IO ()
+main = do
+  name <- ask "What is your name?"
+  let n = 2 * 21
+  greet name
+  print n
+  (x, y) <- return (n, True)
+  if y then print x else return ()
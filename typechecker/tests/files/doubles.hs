@@ -0,0 +1,17 @@
+average :: [Double] -> Double
+average xs = total xs / count xs where { total [] = 0; total (y:ys) = y + total ys; count [] = 0; count (y:ys) = 1 + count ys }
+
+hypotenuse :: Floating a => a -> a -> a
+hypotenuse a b = sqrt (a * a + b * b)
+
+percent :: Int -> Int -> Int
+percent part whole = round (100 * fromIntegral part / fromIntegral whole)
+
+half 0 = 0
+half x = x / 2
+
+average [1.5, 2, 2.5e1]
+hypotenuse 3 4
+percent 1 3
+half 5
+floor 2.5 + 1
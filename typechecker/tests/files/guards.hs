@@ -0,0 +1,20 @@
+data Maybe a = Nothing | Just a
+
+lookup k [] = Nothing
+lookup k ((k2, v):rest)
+  | k == k2 = Just v
+  | otherwise = lookup k rest
+
+find k xs
+  | Just v <- lookup k xs, v > 1 = v
+  | otherwise = 0
+
+classify n
+  | n < small = "small"
+  | n < big = "medium"
+  where
+    small = 10
+    big = 100
+classify n = "large"
+
+(find 'a' [('a', 2)], classify 3)
@@ -0,0 +1,18 @@
+fact :: Integer -> Integer
+fact 0 = 1
+fact n = n * fact (n - 1)
+
+count :: [a] -> Int
+count [] = 0
+count (x:xs) = 1 + count xs
+
+sum :: Num a => [a] -> a
+sum [] = 0
+sum (x:xs) = x + sum xs
+
+average :: [Integer] -> Integer
+average xs = sum xs `div` fromIntegral (count xs)
+
+fact 30
+average [1, 2, 3]
+2 ^ 100
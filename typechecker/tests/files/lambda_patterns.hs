@@ -0,0 +1,11 @@
+data Maybe a = Nothing | Just a
+
+add :: Int -> Int -> Int
+add = \x y -> x + y
+
+swap :: (a, b) -> (b, a)
+swap = \(a, b) -> (b, a)
+
+fromJust = \(Just x) -> x
+
+firsts = \(x:xs) (y:ys) -> (x, y)
@@ -0,0 +1,24 @@
+a = let { x = 1 ; y = x + 1 } in x + y
+
+b = let
+      go 0 acc = acc
+      go n acc = go (n - 1) (acc * n)
+    in go 5 1
+
+c = let
+      isEven 0 = True
+      isEven n = isOdd (n - 1)
+      isOdd 0 = False
+      isOdd n = isEven (n - 1)
+    in (isEven 4, isOdd 4)
+
+d = let
+      same :: Eq a => a -> a -> Bool
+      same u v = u == v
+      ident :: a -> a
+      ident z = z
+    in (same (ident 1) 1, same 'a' (ident 'b'))
+
+e = let ones = 1 : ones in let { take 0 ys = [] ; take k (y:ys) = y : (take (k - 1) ys) } in take 3 ones
+
+(a, b, c, d, e)
@@ -0,0 +1,5 @@
+module Counted where
+
+data Box = Box Int
+
+unbox (Box x) = x
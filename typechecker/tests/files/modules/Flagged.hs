@@ -0,0 +1,5 @@
+module Flagged where
+
+data Box = Box Bool
+
+flagged = Box True
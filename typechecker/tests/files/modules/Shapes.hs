@@ -0,0 +1,17 @@
+module Shapes (Shape(..), area, scale) where
+
+data Shape = Square Int | Rect Int Int
+
+instance Show Shape where
+  show (Square n) = "Square"
+  show (Rect w h) = "Rect"
+
+helper :: Int -> Int -> Int
+helper x y = x * y
+
+area :: Shape -> Int
+area (Square n) = helper n n
+area (Rect w h) = helper w h
+
+scale :: Num a => a -> a -> a
+scale k x = k * x
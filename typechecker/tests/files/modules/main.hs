@@ -0,0 +1,11 @@
+import Shapes
+import qualified Shapes as S (area)
+
+double = scale 2
+
+main :: IO ()
+main = do
+  print (area (Rect 2 3) + S.area (Square 1))
+  print (double 1.5)
+  print (double (area (Square 2)))
+  print (Square 4)
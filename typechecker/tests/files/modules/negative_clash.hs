@@ -0,0 +1,5 @@
+-- Both modules define a type Box with a constructor Box
+import Counted
+import Flagged
+
+main = print (unbox flagged + 1)
@@ -0,0 +1,3 @@
+import Shapes
+
+x = helper 1 2
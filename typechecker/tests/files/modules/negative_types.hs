@@ -0,0 +1,3 @@
+import Shapes
+
+x = area 3
@@ -0,0 +1 @@
+f xs@(x:_) = x && xs
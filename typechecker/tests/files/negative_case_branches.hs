@@ -0,0 +1,3 @@
+sign n = case n of
+  0 -> "zero"
+  _ -> 1
@@ -0,0 +1,3 @@
+isZero n = case n of
+  0 -> True
+  'a' -> False
@@ -0,0 +1 @@
+odds = [x | x <- [1, 2, 3], x `mod` 2]
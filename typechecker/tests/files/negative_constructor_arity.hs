@@ -0,0 +1,3 @@
+data Shape = Circle Int | Rect Int Int
+
+area (Rect w) = w
@@ -0,0 +1,3 @@
+data Shape = Circle Int | Rect Int Int
+
+Rect 1 True
@@ -0,0 +1,3 @@
+main = do
+  putStrLn "one"
+  True
@@ -0,0 +1,4 @@
+data T = A Int
+data U = A Bool
+
+f (A x) = x
@@ -0,0 +1,4 @@
+data T = A Int
+data T = B Bool
+
+f (B x) = x
@@ -0,0 +1,2 @@
+halve :: Int -> Int
+halve x = x / 2
@@ -0,0 +1,5 @@
+f x
+  | x = 1
+  | otherwise = 0
+
+f 3
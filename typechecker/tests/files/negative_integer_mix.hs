@@ -0,0 +1,8 @@
+count :: [a] -> Int
+count [] = 0
+count (x:xs) = 1 + count xs
+
+big :: Integer
+big = 12345678901234567890
+
+big + count [1, 2]
@@ -0,0 +1,4 @@
+data Tree a = Leaf | Node (Tree a) a (Tree a)
+
+size :: Tree -> Int
+size _ = 0
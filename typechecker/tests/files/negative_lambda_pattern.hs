@@ -0,0 +1,2 @@
+f = \(a, b) -> a
+f 1
@@ -0,0 +1,4 @@
+f = let
+      g :: Int -> Int
+      g x = x
+    in g True
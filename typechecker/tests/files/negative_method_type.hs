@@ -0,0 +1,3 @@
+data Color = Red | Green | Blue
+
+instance Show Color where { show c = 'c' }
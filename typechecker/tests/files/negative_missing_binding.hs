@@ -0,0 +1 @@
+f = let { x :: Int } in 3
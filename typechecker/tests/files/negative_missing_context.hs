@@ -0,0 +1,3 @@
+member :: a -> [a] -> Bool
+member x [] = False
+member x (y:ys) = (x == y) || (member x ys)
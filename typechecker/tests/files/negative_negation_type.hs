@@ -0,0 +1 @@
+offset = -True
@@ -0,0 +1,3 @@
+data Color = Red | Green | Blue
+
+Red == Green
@@ -0,0 +1,3 @@
+infixl 6 <+>
+a <+> b = a + b
+True <+> 1
@@ -0,0 +1,3 @@
+(a, b) = (1, True)
+
+c = a && b
@@ -0,0 +1 @@
+mixed = [1..'z']
@@ -0,0 +1 @@
+bools = [False..True]
@@ -0,0 +1 @@
+inc = (\x -> x + 1) :: a -> a
@@ -0,0 +1,2 @@
+f = (&& True)
+f 1
@@ -0,0 +1 @@
+x = (\n -> n + 1) $! True
@@ -0,0 +1,6 @@
+class Pretty a where
+  pretty :: a -> [Char]
+
+-- String is [Char], which an instance can only be given for as a whole [a]
+instance Pretty String where
+  pretty s = s
@@ -0,0 +1 @@
+same = (1, \x -> x) == (1, \x -> x)
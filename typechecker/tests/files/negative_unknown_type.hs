@@ -0,0 +1,4 @@
+f :: Foo -> Int
+f _ = 1
+
+f True
@@ -0,0 +1,5 @@
+f x = y
+  where
+    y = x + 1
+
+y
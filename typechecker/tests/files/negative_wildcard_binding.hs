@@ -0,0 +1 @@
+(_, _) = (1 + True, 2)
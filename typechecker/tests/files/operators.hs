@@ -0,0 +1,24 @@
+data Maybe a = Nothing | Just a
+
+infixr 9 .
+infixr 0 $
+infixl 9 !!
+infixl 3 <|>
+
+(.) :: (b -> c) -> (a -> b) -> a -> c
+(.) f g x = f (g x)
+
+f $ x = f x
+
+(x:xs) !! 0 = x
+(x:xs) !! n = xs !! (n - 1)
+
+Nothing <|> y = y
+Just x <|> y = Just x
+
+inc :: Int -> Int
+inc x = x + 1
+
+twice f = f . f
+
+result = (twice inc) $ [1, 2, 3] !! 2
@@ -0,0 +1,12 @@
+data Maybe a = Nothing | Just a
+
+(a, b) = (1, [2, 3])
+x:xs = "hello"
+
+digits n = let (q, r) = (div n 10, mod n 10) in [q, r]
+
+-- Each variable of a pattern binding is generalised on its own
+both = let (f, g) = (\v -> v, \w -> w) in (f 1, f True, g 'c')
+
+c = case b of
+  d:_ -> a + d
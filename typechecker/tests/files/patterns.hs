@@ -0,0 +1,18 @@
+data Maybe a = Nothing | Just a
+
+pairSums ((a, b):rest) = a + b : pairSums rest
+pairSums [] = []
+
+firstTwo [x, y] = Just (x, y)
+firstTwo _ = Nothing
+
+dup xs@(x:_) = x : xs
+dup [] = []
+
+isZero (Just (-1)) = False
+isZero (Just 0) = True
+isZero _ = False
+
+lastOf l = case l of
+  [x] -> x
+  _:xs@(_:_) -> lastOf xs
@@ -0,0 +1,14 @@
+upTo :: Int -> [Int]
+upTo n = [1..n]
+
+downFrom n = [n, n - 1..0]
+
+letters :: [Char]
+letters = ['a', 'c'..'z']
+
+between :: Enum a => a -> a -> [a]
+between a b = [a..b]
+
+range a b = [a..b]
+
+digits = range '0' '9'
@@ -0,0 +1,23 @@
+map f [] = []
+map f (x:xs) = (f x) : (map f xs)
+
+foldr f z [] = z
+foldr f z (x:xs) = f x (foldr f z xs)
+
+max :: Int -> Int -> Int
+max x y = if x < y then y else x
+
+doubles :: [Int] -> [Int]
+doubles = map (* 2)
+
+sum = foldr (+) 0
+concat = foldr (++) []
+and = foldr (&&) True
+largest = foldr max 0
+copy = foldr (:) []
+
+lessThan :: Ord a => a -> a -> Bool
+lessThan = (<)
+
+clamp x = x `max` 0
+remainders = map (`mod` 3)
@@ -0,0 +1,12 @@
+data Maybe a = Nothing | Just a
+
+sumTo !acc 0 = acc
+sumTo !acc n = sumTo (acc + n) (n - 1)
+
+lazyFst ~(a, b) = a
+
+strictLet = let !x = 1 + 2 in x * 2
+
+forced = (\x -> x + 1) $! 5
+
+both = (Nothing `seq` True) && ([1] `seq` False)
@@ -0,0 +1,20 @@
+initial :: String -> Char
+initial (c:cs) = c
+
+name :: [Char]
+name = "abc" ++ ['d']
+
+shout s = s ++ "!"
+
+letters = ['a'..'z'] == "abc"
+
+type Name = String
+
+greet :: Name -> [Char]
+greet n = 'H' : n
+
+initial name
+shout ['h', 'i']
+show ["a", "b"]
+letters || "b" < name
+greet "i" == "Hi"
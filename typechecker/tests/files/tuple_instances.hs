@@ -0,0 +1,3 @@
+pairs = (1, 'a') == (1, 'b')
+triples = (True, [1], ()) == (True, [1], ())
+shown = show (1, ((), "s"))
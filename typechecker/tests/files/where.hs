@@ -0,0 +1,17 @@
+pair x = (ident x, ident True)
+  where
+    ident z = z
+
+parity n = isEven n
+  where
+    isEven 0 = True
+    isEven m = isOdd (m - 1)
+    isOdd 0 = False
+    isOdd m = isEven (m - 1)
+
+has x ys = helper ys
+  where
+    helper [] = False
+    helper (z:zs) = (z == x) || helper zs
+
+(pair 3, parity 4, has 'a' ['a'], has 2 [1])
@@ -1,5 +1,6 @@
 use log::info;
-use typechecker::typecheck;
+use std::path::Path;
+use typechecker::{typecheck, typecheck_modules};
 
 macro_rules! test_typecheck {
     ($($name:ident: $file:expr,)*) => {
@@ -44,9 +45,85 @@ test_typecheck! {
     polymorphic_decl: "files/polymorphic_decl.hs",
     polymorphic_tuple: "files/polymorphic_tuple.hs",
     polymorphic_let: "files/polymorphic_let.hs",
+    data: "files/data.hs",
+    classes: "files/classes.hs",
+    where_block: "files/where.hs",
+    guards: "files/guards.hs",
+    let_bindings: "files/let_bindings.hs",
+    lambda_patterns: "files/lambda_patterns.hs",
+    sections: "files/sections.hs",
+    operators: "files/operators.hs",
+    comprehensions: "files/comprehensions.hs",
+    ranges: "files/ranges.hs",
+    arithmetic: "files/arithmetic.hs",
+    integers: "files/integers.hs",
+    doubles: "files/doubles.hs",
+    case_of: "files/case.hs",
+    do_notation: "files/do.hs",
+    annotations: "files/annotations.hs",
+    strings: "files/strings.hs",
+    patterns: "files/patterns.hs",
+    pattern_bindings: "files/pattern_bindings.hs",
+    strictness: "files/strictness.hs",
+    tuple_instances: "files/tuple_instances.hs",
 }
 
 test_typecheck_negatives! {
     negative_arg_types_direct: "files/negative_arg_types_direct.hs",
     negative_arg_types_indirect: "files/negative_arg_types_indirect.hs",
+    negative_constructor_field: "files/negative_constructor_field.hs",
+    negative_constructor_arity: "files/negative_constructor_arity.hs",
+    negative_kind: "files/negative_kind.hs",
+    negative_unknown_type: "files/negative_unknown_type.hs",
+    negative_no_instance: "files/negative_no_instance.hs",
+    negative_missing_context: "files/negative_missing_context.hs",
+    negative_method_type: "files/negative_method_type.hs",
+    negative_where_scope: "files/negative_where_scope.hs",
+    negative_guard_type: "files/negative_guard_type.hs",
+    negative_local_signature: "files/negative_local_signature.hs",
+    negative_missing_binding: "files/negative_missing_binding.hs",
+    negative_lambda_pattern: "files/negative_lambda_pattern.hs",
+    negative_section_type: "files/negative_section_type.hs",
+    negative_operator_type: "files/negative_operator_type.hs",
+    negative_comprehension_guard: "files/negative_comprehension_guard.hs",
+    negative_range_type: "files/negative_range_type.hs",
+    negative_range_bounds: "files/negative_range_bounds.hs",
+    negative_negation_type: "files/negative_negation_type.hs",
+    negative_integer_mix: "files/negative_integer_mix.hs",
+    negative_fractional_int: "files/negative_fractional_int.hs",
+    negative_case_branches: "files/negative_case_branches.hs",
+    negative_case_pattern: "files/negative_case_pattern.hs",
+    negative_main_type: "files/negative_main_type.hs",
+    negative_do_statement: "files/negative_do_statement.hs",
+    negative_annotation_type: "files/negative_annotation_type.hs",
+    negative_rigid_annotation: "files/negative_rigid_annotation.hs",
+    negative_escaped_annotation: "files/negative_escaped_annotation.hs",
+    negative_string_elements: "files/negative_string_elements.hs",
+    negative_as_pattern: "files/negative_as_pattern.hs",
+    negative_pattern_binding: "files/negative_pattern_binding.hs",
+    negative_wildcard_binding: "files/negative_wildcard_binding.hs",
+    negative_strict_application: "files/negative_strict_application.hs",
+    negative_tuple_instance: "files/negative_tuple_instance.hs",
+    negative_string_instance: "files/negative_string_instance.hs",
+    negative_duplicate_type: "files/negative_duplicate_type.hs",
+    negative_duplicate_constructor: "files/negative_duplicate_constructor.hs",
+}
+
+#[test]
+fn modules() {
+    let _ = env_logger::try_init();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/files/modules");
+    let modules = parser::load(&dir.join("main.hs"), &[]).unwrap();
+    assert!(typecheck_modules(&modules).is_ok());
+    let modules = parser::load(&dir.join("negative_private.hs"), &[]).unwrap();
+    let err = typecheck_modules(&modules).unwrap_err();
+    assert_eq!(err.to_string(), "Cannot find type of helper");
+    let modules = parser::load(&dir.join("negative_types.hs"), &[]).unwrap();
+    assert!(typecheck_modules(&modules).is_err());
+    let modules = parser::load(&dir.join("negative_clash.hs"), &[]).unwrap();
+    let err = typecheck_modules(&modules).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Box is defined by both module Counted and module Flagged"
+    );
 }